@@ -3,6 +3,7 @@ use eevee::{
     genome::{Recurrent, WConnection},
     population::speciate,
     reproduce::population_alloc,
+    EvolutionConfig,
 };
 
 type C = WConnection;
@@ -15,6 +16,9 @@ fn bench_alloc(bench: &mut Criterion) {
             .unwrap()
             .into_iter(),
         vec![].into_iter(),
+        EvolutionConfig::default().specie_threshold,
+        &mut 0,
+        0,
     );
 
     bench.bench_function("alloc", |b| {
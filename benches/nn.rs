@@ -2,13 +2,66 @@
 #![allow(confusable_idents)]
 
 use criterion::Criterion;
-use eevee::{activate::relu, network::Continuous, Network};
+use eevee::{
+    genome::{Genome, InnoGen, Recurrent, WConnection},
+    network::{Continuous, FromGenome, NonBias, SparseContinuous, SparseNonBias},
+    random::default_rng,
+    Connection, EvolutionConfig, Network,
+};
+use rand::Rng;
 
 fn bench_nn(bench: &mut Criterion) {
     let net = &mut Continuous::from_str(include_str!("data/ctrnn-rand-100.json")).unwrap();
     let i = vec![0.7, 0.3];
 
-    bench.bench_function("ctrnn-step", |b| b.iter(|| net.step(100, &i, relu)));
+    bench.bench_function("ctrnn-step", |b| b.iter(|| net.step(100, &i)));
+
+    let net = &mut SparseContinuous::from(
+        Continuous::from_str(include_str!("data/ctrnn-rand-100.json")).unwrap(),
+    );
+    bench.bench_function("ctrnn-sparse-step", |b| b.iter(|| net.step(100, &i)));
+}
+
+/// A genome shaped like those evolved for nes-tetris, with 200 sensory nodes and only a few
+/// hundred enabled connections
+fn sparse_genome() -> Recurrent<WConnection> {
+    let mut rng = default_rng();
+    let config = EvolutionConfig::default();
+    let (mut genome, inno_head) = Recurrent::<WConnection>::new(200, 8);
+    let mut inno = InnoGen::new(inno_head);
+    for c in genome.connections_mut() {
+        if rng.random_bool(0.9) {
+            c.disable();
+        }
+    }
+    for _ in 0..200 {
+        let _ = genome.mutate(&mut rng, &mut inno, &config.mutation);
+    }
+
+    genome
+}
+
+fn bench_sparse(bench: &mut Criterion) {
+    let genome = sparse_genome();
+    let i = vec![0.5; 200];
+
+    let net = &mut Continuous::from_genome(&genome);
+    bench.bench_function("ctrnn-dense-sparse-genome-step", |b| {
+        b.iter(|| net.step(10, &i))
+    });
+    let net = &mut SparseContinuous::from_genome(&genome);
+    bench.bench_function("ctrnn-sparse-sparse-genome-step", |b| {
+        b.iter(|| net.step(10, &i))
+    });
+
+    let net = &mut NonBias::from_genome(&genome);
+    bench.bench_function("nonbias-dense-sparse-genome-step", |b| {
+        b.iter(|| net.step(10, &i))
+    });
+    let net = &mut SparseNonBias::from_genome(&genome);
+    bench.bench_function("nonbias-sparse-sparse-genome-step", |b| {
+        b.iter(|| net.step(10, &i))
+    });
 }
 
 pub fn benches() {
@@ -27,6 +80,7 @@ pub fn benches() {
             .configure_from_args()
     };
     bench_nn(&mut criterion);
+    bench_sparse(&mut criterion);
 }
 
 fn main() {
@@ -3,6 +3,7 @@ use eevee::{
     genome::{Connection, Genome, InnoGen, Recurrent, WConnection},
     random::default_rng,
     reproduce::reproduce,
+    EvolutionConfig,
 };
 
 type C = WConnection;
@@ -25,8 +26,17 @@ fn bench_reproduce(bench: &mut Criterion) {
         .unwrap();
 
     let mut rng = default_rng();
+    let config = EvolutionConfig::default();
     bench.bench_function("reproduce", |b| {
-        b.iter(|| reproduce(genomes.clone(), 100, &mut InnoGen::new(inno_head), &mut rng))
+        b.iter(|| {
+            reproduce(
+                genomes.clone(),
+                100,
+                &mut InnoGen::new(inno_head),
+                &mut rng,
+                &config,
+            )
+        })
     });
 }
 
@@ -4,6 +4,7 @@ use eevee::{
     crossover::{avg_param_diff, disjoint_excess_count},
     genome::{Recurrent, WConnection},
     population::speciate,
+    EvolutionConfig,
 };
 
 type C = WConnection;
@@ -28,7 +29,15 @@ fn bench_speciate(bench: &mut Criterion) {
     let genomes =
         serde_json::from_str::<Vec<(G, _)>>(include_str!("data/ctr-genome-xor-100.json")).unwrap();
     bench.bench_function("speciate", |b| {
-        b.iter(|| speciate(genomes.iter().cloned(), empty()))
+        b.iter(|| {
+            speciate(
+                genomes.iter().cloned(),
+                empty(),
+                EvolutionConfig::default().specie_threshold,
+                &mut 0,
+                0,
+            )
+        })
     });
 }
 
@@ -3,13 +3,13 @@
 
 use core::ops::ControlFlow;
 use eevee::{
-    activate::relu,
+    activate::Activation,
+    config::MutationConfig,
     genome::{Genome, Recurrent, WConnection},
     network::{Continuous, ToNetwork},
-    population::{population_from_files, population_init, population_to_files},
-    random::default_rng,
-    scenario::{evolve, EvolutionHooks},
-    Connection, Network, Scenario, Stats,
+    population::population_init_with,
+    random::{seed_urandom, WyRng},
+    Connection, EvolutionConfig, Evolver, Network, Scenario, Stats,
 };
 use nes_rust_slim::{
     button::Button, default_audio::DefaultAudio, default_display::DefaultDisplay,
@@ -80,7 +80,7 @@ fn sense_board(ram: &[u8], sense: &mut [f64; INPUT_SIZE]) {
             .filter_map(|(row, col)| {
                 let row = row + ram[Y];
                 let col = col + ram[X];
-                (row >= 2 && col >= 2).then(|| (((row - 2) as usize * 10) + (col - 2) as usize))
+                (row >= 2 && col >= 2).then(|| ((row - 2) as usize * 10) + (col - 2) as usize)
             })
             .filter(|index| *index < 200)
         {
@@ -146,14 +146,12 @@ fn enter_game(nes: &mut Nes) {
 
 struct NesTetris;
 
-impl<C: Connection, G: Genome<C> + ToNetwork<Continuous, C>, A: Fn(f64) -> f64> Scenario<C, G, A>
-    for NesTetris
-{
+impl<C: Connection, G: Genome<C> + ToNetwork<Continuous, C>> Scenario<C, G> for NesTetris {
     fn io(&self) -> (usize, usize) {
         (200, 8)
     }
 
-    fn eval(&self, genome: &G, σ: &A) -> f64 {
+    fn eval(&self, genome: &G) -> f64 {
         let mut nes = Nes::new(
             Box::new(DefaultInput::new()),
             Box::new(DefaultDisplay::new()),
@@ -167,7 +165,7 @@ impl<C: Connection, G: Genome<C> + ToNetwork<Continuous, C>, A: Fn(f64) -> f64>
         let mut sense = [0.; 200];
         while nes.get_cpu().get_ram().data[GAME_OVER] == 0 {
             sense_board(&nes.get_cpu().get_ram().data, &mut sense);
-            network.step(1, &sense, σ);
+            network.step(1, &sense);
 
             for (idx, x) in network.output().iter().enumerate() {
                 if idx == 2 || idx == 3 {
@@ -193,23 +191,18 @@ impl<C: Connection, G: Genome<C> + ToNetwork<Continuous, C>, A: Fn(f64) -> f64>
 }
 
 const POPULATION: usize = 1000;
+const CHECKPOINT: &str = "output/nes-tetris/checkpoint.json";
 
 fn hook(stats: &mut Stats<'_, WConnection, Recurrent<WConnection>>) -> ControlFlow<()> {
-    if stats.generation % 10 != 0 {
-        ControlFlow::Continue(())
-    } else {
+    if stats.generation.is_multiple_of(10) {
         let fittest = stats.fittest().unwrap();
         println!("gen {} best: {:.3}", stats.generation, fittest.1);
+    }
 
-        if stats.generation % 10 == 0 {
-            population_to_files("output/sentiment", stats.species).unwrap();
-        }
-
-        if stats.generation == 400 {
-            ControlFlow::Break(())
-        } else {
-            ControlFlow::Continue(())
-        }
+    if stats.generation == 400 {
+        ControlFlow::Break(())
+    } else {
+        ControlFlow::Continue(())
     }
 }
 
@@ -219,14 +212,31 @@ fn main() {
 
     create_dir_all("output/nes-tetris").expect("failed to create genome output");
 
-    evolve(
-        NesTetris {},
-        |(i, o)| {
-            population_from_files("output/nes-tetris")
-                .unwrap_or_else(|_| population_init::<C, G>(i, o, POPULATION))
-        },
-        relu,
-        default_rng(),
-        EvolutionHooks::new(vec![Box::new(hook)]),
-    );
+    let scenario = NesTetris {};
+    let mut evolver = Evolver::from_file(CHECKPOINT).unwrap_or_else(|_| {
+        let (i, o) = Scenario::<C, G>::io(&scenario);
+        Evolver::new(
+            population_init_with::<C, G>(i, o, POPULATION, Activation::Relu),
+            EvolutionConfig {
+                mutation: MutationConfig {
+                    activation: Activation::Relu,
+                    ..MutationConfig::default()
+                },
+                ..EvolutionConfig::default()
+            },
+            WyRng::seeded(seed_urandom().unwrap()),
+        )
+    });
+
+    loop {
+        evolver.eval(&scenario);
+        if hook(&mut evolver.stats()).is_break() {
+            break;
+        }
+
+        evolver.step().unwrap();
+        if evolver.generation().is_multiple_of(10) {
+            evolver.to_file(CHECKPOINT).unwrap();
+        }
+    }
 }
@@ -3,13 +3,14 @@
 
 use core::f64;
 use eevee::{
-    activate::relu,
+    activate::Activation,
+    config::MutationConfig,
     genome::{Recurrent, WConnection},
     network::{loss::decay_linear, Continuous, ToNetwork},
-    population::{population_from_files, population_init, population_to_files},
+    population::{population_from_files, population_init_with, population_to_files},
     random::default_rng,
     scenario::{evolve, EvolutionHooks},
-    Connection, Genome, Network, Scenario, Stats,
+    Connection, EvolutionConfig, Genome, Network, Scenario, Stats,
 };
 use std::{fs::create_dir_all, ops::ControlFlow};
 
@@ -90,21 +91,19 @@ fn chunked(chunk_size: usize, data: &str) -> Vec<Vec<f64>> {
         .collect::<Vec<Vec<_>>>()
 }
 
-impl<'a, C: Connection, G: Genome<C> + ToNetwork<Continuous, C>, A: Fn(f64) -> f64>
-    Scenario<C, G, A> for Sentiment<'a>
-{
+impl<'a, C: Connection, G: Genome<C> + ToNetwork<Continuous, C>> Scenario<C, G> for Sentiment<'a> {
     fn io(&self) -> (usize, usize) {
         (8 * self.chunk_size, 2)
     }
 
-    fn eval(&self, genome: &G, σ: &A) -> f64 {
+    fn eval(&self, genome: &G) -> f64 {
         let mut network = genome.network();
         let fit = self
             .data
             .iter()
             .map(|(_, input, kind)| {
                 for chunk in input {
-                    network.step(5, chunk, σ);
+                    network.step(5, chunk);
                 }
 
                 let [w_positive, w_negative] = kind.value();
@@ -124,7 +123,7 @@ fn hook<C: Connection, G: Genome<C>>(stats: &mut Stats<'_, C, G>) -> ControlFlow
     let fittest = stats.fittest().unwrap();
     println!("fittest of gen {}: {:.4}", stats.generation, fittest.1);
 
-    if stats.generation % 10 == 0 {
+    if stats.generation.is_multiple_of(10) {
         population_to_files("output/sentiment", stats.species).unwrap();
     }
 
@@ -147,11 +146,19 @@ fn main() {
     evolve(
         Sentiment::new(8, positive, negative),
         |(i, o)| {
-            population_from_files("output/sentiment")
-                .unwrap_or_else(|_| population_init::<C, G>(i, o, POPULATION))
+            population_from_files("output/sentiment").unwrap_or_else(|_| {
+                population_init_with::<C, G>(i, o, POPULATION, Activation::Relu)
+            })
+        },
+        EvolutionConfig {
+            mutation: MutationConfig {
+                activation: Activation::Relu,
+                ..MutationConfig::default()
+            },
+            ..EvolutionConfig::default()
         },
-        relu,
         default_rng(),
         EvolutionHooks::new(vec![Box::new(hook)]),
-    );
+    )
+    .expect("failed to evolve");
 }
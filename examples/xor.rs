@@ -3,13 +3,14 @@
 
 use core::{f64, ops::ControlFlow};
 use eevee::{
-    activate::relu,
+    activate::Activation,
+    config::MutationConfig,
     genome::{Genome, Recurrent, WConnection},
     network::{Network, Simple, ToNetwork},
-    population::population_init,
+    population::population_init_with,
     random::default_rng,
     scenario::{evolve, EvolutionHooks},
-    Connection, Scenario, Stats,
+    Connection, EvolutionConfig, Scenario, Stats,
 };
 
 const POPULATION: usize = 1000;
@@ -17,8 +18,8 @@ const POPULATION: usize = 1000;
 struct Xor;
 
 macro_rules! eval_pair {
-    ($pair:expr, $want:expr, ($network:ident $fit:ident $σ:ident)) => {{
-        $network.step(2, &$pair, $σ);
+    ($pair:expr, $want:expr, ($network:ident $fit:ident)) => {{
+        $network.step(2, &$pair);
         let v = $network.output()[0];
         if (0. ..=1.).contains(&v) {
             let error = ($want - v).abs();
@@ -31,21 +32,19 @@ macro_rules! eval_pair {
     }};
 }
 
-impl<C: Connection, G: Genome<C> + ToNetwork<Simple<C>, C>, A: Fn(f64) -> f64> Scenario<C, G, A>
-    for Xor
-{
+impl<C: Connection, G: Genome<C> + ToNetwork<Simple<C>, C>> Scenario<C, G> for Xor {
     fn io(&self) -> (usize, usize) {
         (2, 1)
     }
 
-    fn eval(&self, genome: &G, σ: &A) -> f64 {
+    fn eval(&self, genome: &G) -> f64 {
         let mut network = genome.network();
         let mut fit = 0.;
 
-        eval_pair!([0., 0.], 1., (network fit σ));
-        eval_pair!([1., 1.], 1., (network fit σ));
-        eval_pair!([1., 0.], 0., (network fit σ));
-        eval_pair!([0., 1.], 0., (network fit σ));
+        eval_pair!([0., 0.], 1., (network fit));
+        eval_pair!([1., 1.], 1., (network fit));
+        eval_pair!([1., 0.], 0., (network fit));
+        eval_pair!([0., 1.], 0., (network fit));
 
         fit
     }
@@ -55,10 +54,13 @@ fn hook<C: Connection, G: Genome<C>>(stats: &mut Stats<'_, C, G>) -> ControlFlow
     if stats.generation % 100 == 1 {
         let (_, f) = stats.fittest().unwrap();
         println!(
-            "fittest of gen {}: {:.4} (of {} species",
+            "fittest of gen {}: {:.4} (mean {:.4}, of {} species, threshold {:.2}, {:.1} connections)",
             stats.generation,
             f,
-            stats.species.len()
+            stats.fitness_mean().unwrap(),
+            stats.species.len(),
+            stats.threshold,
+            stats.connections_mean().unwrap(),
         );
     }
 
@@ -82,9 +84,18 @@ type G = Recurrent<C>;
 fn main() {
     evolve(
         Xor {},
-        |(i, o)| population_init::<C, G>(i, o, POPULATION),
-        relu,
+        |(i, o)| population_init_with::<C, G>(i, o, POPULATION, Activation::Relu),
+        EvolutionConfig {
+            mutation: MutationConfig {
+                mutate_connection: 75,
+                mutate_activation: 5,
+                activation: Activation::Relu,
+                ..MutationConfig::default()
+            },
+            ..EvolutionConfig::default()
+        },
         default_rng(),
         EvolutionHooks::new(vec![Box::new(hook)]),
-    );
+    )
+    .expect("failed to evolve");
 }
@@ -0,0 +1,669 @@
+//! Competitive coevolution, where rather than being scored in isolation, genomes are scored by
+//! playing matches against each other. Useful for games, whose difficulty should grow with the
+//! skill of the players.
+//!
+//! Genomes may compete within a single population, or against the genomes of a second one (
+//! hosts and parasites, predators and prey ). Who plays who is decided by [Sampling], and a
+//! genome's fitness is its mean score over every match that it played.
+
+use crate::{
+    error::EvolutionError,
+    genome::{Connection, Genome, InnoGen},
+    population::{FittedGroup, SpecieGroup},
+    scenario::Evolver,
+    Specie,
+};
+use core::{error::Error, mem::take, ops::ControlFlow};
+use rand::{seq::index::sample, RngCore};
+#[cfg(feature = "parallel")]
+use rayon::{
+    iter::{IntoParallelRefIterator, ParallelIterator},
+    ThreadPool, ThreadPoolBuilder,
+};
+use serde::{Deserialize, Serialize};
+use std::{fs, path::Path};
+
+/// Like a [Scenario](crate::scenario::Scenario), but evaluating genomes by playing them
+/// against each other in matches of [players](CoevolutionScenario::players) genomes.
+pub trait CoevolutionScenario<C: Connection, G: Genome<C>> {
+    fn io(&self) -> (usize, usize);
+
+    /// Number of genomes who play in each match
+    fn players(&self) -> usize {
+        2
+    }
+
+    /// Play a match between `players`, scoring each of them in the same order. Greater scores
+    /// will be optimized for.
+    fn eval(&self, players: &[&G]) -> Vec<f64>;
+}
+
+/// How opponents are picked for each genome. Every genome plays in the first seat of its own
+/// matches, and its opponents fill the rest. A genome competing within a single population
+/// never plays against itself, and a genome of 2 populations only plays against the other.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum Sampling {
+    /// Play every combination of opponents, which grows quickly with the number of players
+    RoundRobin,
+    /// Play some number of matches against opponents picked at random
+    Random { matches: usize },
+    /// Play some number of matches against opponents picked at random from the champions of
+    /// past generations, up to `size` of the most recent of whom are remembered. Opponents
+    /// are picked from the current generation until there are enough champions.
+    HallOfFame { matches: usize, size: usize },
+}
+
+/// Somebody playing in a match
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Player {
+    /// A genome of the current generation of some population
+    Member(usize, usize),
+    /// A champion of some population
+    Champion(usize, usize),
+}
+
+/// Every combination of `k` indices in `0..n`, in lexicographic order
+fn combinations(n: usize, k: usize) -> Vec<Vec<usize>> {
+    if k > n {
+        return vec![];
+    }
+
+    let mut combos = Vec::new();
+    let mut combo = (0..k).collect::<Vec<_>>();
+    loop {
+        combos.push(combo.clone());
+        let Some(pos) = (0..k).rev().find(|pos| combo[*pos] != pos + n - k) else {
+            return combos;
+        };
+
+        combo[pos] += 1;
+        for next in pos + 1..k {
+            combo[next] = combo[next - 1] + 1;
+        }
+    }
+}
+
+/// A stepwise handle on the coevolution of one or two populations, each an [Evolver]. Like an
+/// evolver, a coevolver whose rng is serializable is a checkpoint of itself.
+#[derive(Serialize, Deserialize)]
+#[serde(bound(serialize = "R: Serialize", deserialize = "R: Deserialize<'de>"))]
+pub struct Coevolver<C: Connection, G: Genome<C>, R: RngCore> {
+    populations: Vec<Evolver<C, G, R>>,
+    sampling: Sampling,
+    /// champions of each population, oldest first
+    champions: Vec<Vec<G>>,
+    /// rng used to pick opponents
+    rng: R,
+    #[cfg(feature = "parallel")]
+    #[serde(skip, default = "thread_pool")]
+    thread_pool: ThreadPool,
+}
+
+#[cfg(feature = "parallel")]
+fn thread_pool() -> ThreadPool {
+    ThreadPoolBuilder::new().build().unwrap()
+}
+
+impl<C: Connection, G: Genome<C>, R: RngCore> Coevolver<C, G, R> {
+    /// Coevolve either one population against itself, or two against each other. Opponents are
+    /// picked with `rng`, and each population evolves with its own.
+    pub fn new(
+        populations: Vec<Evolver<C, G, R>>,
+        sampling: Sampling,
+        rng: R,
+    ) -> Result<Self, Box<dyn Error>> {
+        if !(1..=2).contains(&populations.len()) {
+            return Err(format!(
+                "can only coevolve 1 or 2 populations, not {}",
+                populations.len()
+            )
+            .into());
+        }
+
+        Ok(Self {
+            champions: vec![Vec::new(); populations.len()],
+            populations,
+            sampling,
+            rng,
+            #[cfg(feature = "parallel")]
+            thread_pool: thread_pool(),
+        })
+    }
+
+    pub fn populations(&self) -> &[Evolver<C, G, R>] {
+        &self.populations
+    }
+
+    /// Champions of past generations of population `idx`, oldest first. Only remembered when
+    /// sampling from a [HallOfFame](Sampling::HallOfFame).
+    pub fn champions(&self, idx: usize) -> &[G] {
+        &self.champions[idx]
+    }
+
+    pub fn sampling(&self) -> &Sampling {
+        &self.sampling
+    }
+
+    pub fn generation(&self) -> usize {
+        self.populations[0].generation()
+    }
+
+    /// Population whose genomes are the opponents of population `idx`
+    fn rival(&self, idx: usize) -> usize {
+        (idx + 1) % self.populations.len()
+    }
+
+    /// Every match to be played this generation
+    fn schedule(&mut self, players: usize) -> Vec<Vec<Player>> {
+        let mut schedule = Vec::new();
+        for pop in 0..self.populations.len() {
+            let rival = self.rival(pop);
+            let pool = self.populations[rival].ask().len();
+            for focal in 0..self.populations[pop].ask().len() {
+                // opponents by their index in the rival pool, skipping ourselves
+                let member = |idx: usize| {
+                    Player::Member(
+                        rival,
+                        if rival == pop && idx >= focal {
+                            idx + 1
+                        } else {
+                            idx
+                        },
+                    )
+                };
+                let pool = if rival == pop { pool - 1 } else { pool };
+                let seat =
+                    |opponents: Vec<Player>| [vec![Player::Member(pop, focal)], opponents].concat();
+
+                let (matches, champions) = match self.sampling {
+                    Sampling::RoundRobin => {
+                        for combo in combinations(pool, players - 1) {
+                            schedule.push(seat(combo.into_iter().map(member).collect()));
+                        }
+                        continue;
+                    }
+                    Sampling::Random { matches } => (matches, 0),
+                    Sampling::HallOfFame { matches, .. } => (matches, self.champions[rival].len()),
+                };
+
+                for _ in 0..matches {
+                    let opponents = if champions >= players - 1 {
+                        sample(&mut self.rng, champions, players - 1)
+                            .into_iter()
+                            .map(|idx| Player::Champion(rival, idx))
+                            .collect()
+                    } else if pool >= players - 1 {
+                        sample(&mut self.rng, pool, players - 1)
+                            .into_iter()
+                            .map(member)
+                            .collect()
+                    } else {
+                        continue;
+                    };
+                    schedule.push(seat(opponents));
+                }
+            }
+        }
+
+        schedule
+    }
+
+    /// Remember the fittest genome of each population, if sampling from a hall of fame
+    fn crown(&mut self) {
+        let Sampling::HallOfFame { size, .. } = self.sampling else {
+            return;
+        };
+
+        for (champions, population) in self.champions.iter_mut().zip(&self.populations) {
+            let fittest = population
+                .species()
+                .iter()
+                .filter_map(Specie::fittest)
+                .max_by(|(_, l), (_, r)| l.total_cmp(r));
+            if let Some((genome, _)) = fittest {
+                champions.push(genome.clone());
+            }
+            if champions.len() > size {
+                champions.drain(..champions.len() - size);
+            }
+        }
+    }
+
+    /// Reproduce every population into its next generation. Fails if any population hasn't
+    /// been evaluated yet, or if any population fails to step, in which case none of them have
+    /// stepped, and nothing but their rngs have changed.
+    pub fn step(&mut self) -> Result<(), EvolutionError> {
+        if let Some(population) = self.populations.iter().find(|p| !p.ask().is_empty()) {
+            return Err(EvolutionError::Unevaluated {
+                pending: population.ask().len(),
+                generation: population.generation(),
+            });
+        }
+
+        let mut innogen = InnoGen::new(
+            self.populations
+                .iter()
+                .map(Evolver::inno_head)
+                .max()
+                .unwrap(),
+        );
+        let staged = self
+            .populations
+            .iter_mut()
+            .map(|population| population.stage(&mut innogen))
+            .collect::<Result<Vec<_>, _>>()?;
+        for (population, staged) in self.populations.iter_mut().zip(staged) {
+            population.commit(staged);
+        }
+
+        Ok(())
+    }
+
+    /// Stop evolving, and take each population's most recently evaluated species and
+    /// innovation head
+    pub fn into_parts(self) -> Vec<(Vec<Specie<C, G>>, usize)> {
+        self.populations
+            .into_iter()
+            .map(Evolver::into_parts)
+            .collect()
+    }
+}
+
+impl<
+        #[cfg(not(feature = "parallel"))] C: Connection,
+        #[cfg(feature = "parallel")] C: Connection + Sync,
+        #[cfg(not(feature = "parallel"))] G: Genome<C>,
+        #[cfg(feature = "parallel")] G: Genome<C> + Send + Sync,
+        R: RngCore,
+    > Coevolver<C, G, R>
+{
+    /// Play every match of this generation with `scenario`, and [tell](Evolver::tell) each
+    /// population the mean score of its genomes. Genomes who played no matches score 0. Fails
+    /// if matches have no players, or if any match isn't scored for each of its players. If
+    /// compiled with `--features parallel`, matches are played in a thread-pool of one thread
+    /// per cpu on the host.
+    pub fn eval<
+        #[cfg(not(feature = "parallel"))] S: CoevolutionScenario<C, G>,
+        #[cfg(feature = "parallel")] S: CoevolutionScenario<C, G> + Sync,
+    >(
+        &mut self,
+        scenario: &S,
+    ) -> Result<(), EvolutionError> {
+        let players = scenario.players();
+        if players == 0 {
+            return Err(EvolutionError::NoPlayers);
+        }
+
+        let schedule = self.schedule(players);
+        let matches = schedule
+            .iter()
+            .map(|players| {
+                players
+                    .iter()
+                    .map(|player| match *player {
+                        Player::Member(pop, idx) => &self.populations[pop].ask()[idx],
+                        Player::Champion(pop, idx) => &self.champions[pop][idx],
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+        let play = |players: &Vec<&G>| {
+            let scores = scenario.eval(players);
+            if scores.len() == players.len() {
+                Ok(scores)
+            } else {
+                Err(EvolutionError::Mismatched {
+                    wanted: players.len(),
+                    got: scores.len(),
+                })
+            }
+        };
+
+        #[cfg(not(feature = "parallel"))]
+        let scores = matches.iter().map(play).collect::<Result<Vec<_>, _>>()?;
+        #[cfg(feature = "parallel")]
+        let scores = self
+            .thread_pool
+            .install(|| matches.par_iter().map(play).collect::<Result<Vec<_>, _>>())?;
+
+        let mut totals = self
+            .populations
+            .iter()
+            .map(|p| vec![(0., 0); p.ask().len()])
+            .collect::<Vec<_>>();
+        for (players, scores) in schedule.iter().zip(scores) {
+            for (player, score) in players.iter().zip(scores) {
+                if let Player::Member(pop, idx) = player {
+                    let (sum, count) = &mut totals[*pop][*idx];
+                    *sum += score;
+                    *count += 1;
+                }
+            }
+        }
+
+        for (population, totals) in self.populations.iter_mut().zip(take(&mut totals)) {
+            population.tell(totals.into_iter().map(|(sum, count)| {
+                if count == 0 {
+                    0.
+                } else {
+                    sum / count as f64
+                }
+            }))?;
+        }
+
+        self.crown();
+        Ok(())
+    }
+}
+
+impl<C: Connection, G: Genome<C>, R: RngCore + Serialize + for<'de> Deserialize<'de>>
+    Coevolver<C, G, R>
+{
+    /// Serialize this coevolver to a JSON string
+    pub fn to_string(&self) -> Result<String, Box<dyn Error>> {
+        Ok(serde_json::to_string(self)?)
+    }
+
+    /// Deserialize a coevolver from a JSON string
+    #[allow(clippy::should_implement_trait)]
+    pub fn from_str(s: &str) -> Result<Self, Box<dyn Error>> {
+        serde_json::from_str(s).map_err(|op| op.into())
+    }
+
+    /// Checkpoint the complete coevolution state to a file at `path`
+    pub fn to_file<P: AsRef<Path>>(&self, path: P) -> Result<(), Box<dyn Error>> {
+        fs::write(path, self.to_string()?)?;
+        Ok(())
+    }
+
+    /// Resume coevolution from a checkpoint written by [to_file](Coevolver::to_file)
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self, Box<dyn Error>> {
+        Self::from_str(&fs::read_to_string(path)?)
+    }
+}
+
+/// The coevolution counterpart to [evolve](crate::scenario::evolve), evolving the populations
+/// of `coevolver` with `scenario`. `hook` is called after each generation is evaluated, and may
+/// halt evolution by returning a ControlFlow::Break. Fails if the scenario doesn't score every
+/// player of its matches, or if some generation can't be reproduced.
+pub fn coevolve<
+    #[cfg(not(feature = "parallel"))] C: Connection,
+    #[cfg(feature = "parallel")] C: Connection + Sync,
+    #[cfg(not(feature = "parallel"))] G: Genome<C>,
+    #[cfg(feature = "parallel")] G: Genome<C> + Send + Sync,
+    #[cfg(not(feature = "parallel"))] S: CoevolutionScenario<C, G>,
+    #[cfg(feature = "parallel")] S: CoevolutionScenario<C, G> + Sync,
+    R: RngCore,
+>(
+    scenario: S,
+    mut coevolver: Coevolver<C, G, R>,
+    mut hook: impl FnMut(&Coevolver<C, G, R>) -> ControlFlow<()>,
+) -> Result<Vec<SpecieGroup<C, G>>, EvolutionError> {
+    loop {
+        coevolver.eval(&scenario)?;
+        if hook(&coevolver).is_break() {
+            break Ok(coevolver.into_parts());
+        }
+
+        coevolver.step()?;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        config::EvolutionConfig,
+        genome::{Recurrent, WConnection},
+        population::population_init,
+        random::WyRng,
+        test_t,
+    };
+    use rand::Rng;
+    use std::sync::Mutex;
+
+    type C = WConnection;
+    type G = Recurrent<C>;
+
+    /// Whoever has the greatest enabled weight sum wins
+    struct Heaviest;
+
+    fn weight<C: Connection, G: Genome<C>>(genome: &G) -> f64 {
+        genome
+            .connections()
+            .iter()
+            .filter(|c| c.enabled())
+            .map(|c| c.weight())
+            .sum()
+    }
+
+    impl<C: Connection, G: Genome<C>> CoevolutionScenario<C, G> for Heaviest {
+        fn io(&self) -> (usize, usize) {
+            (2, 1)
+        }
+
+        fn eval(&self, players: &[&G]) -> Vec<f64> {
+            let best = players.iter().map(|p| weight(*p)).fold(f64::MIN, f64::max);
+            players
+                .iter()
+                .map(|p| if weight(*p) == best { 1. } else { 0. })
+                .collect()
+        }
+    }
+
+    /// Records the weight sum of every player in every match
+    struct Recorder(Mutex<Vec<Vec<f64>>>, usize);
+
+    impl<C: Connection, G: Genome<C>> CoevolutionScenario<C, G> for Recorder {
+        fn io(&self) -> (usize, usize) {
+            (2, 1)
+        }
+
+        fn players(&self) -> usize {
+            self.1
+        }
+
+        fn eval(&self, players: &[&G]) -> Vec<f64> {
+            self.0
+                .lock()
+                .unwrap()
+                .push(players.iter().map(|p| weight(*p)).collect());
+            vec![0.; players.len()]
+        }
+    }
+
+    /// Scores a single player of every match, however many there are
+    struct Miscount(usize);
+
+    impl<C: Connection, G: Genome<C>> CoevolutionScenario<C, G> for Miscount {
+        fn io(&self) -> (usize, usize) {
+            (2, 1)
+        }
+
+        fn players(&self) -> usize {
+            self.0
+        }
+
+        fn eval(&self, _: &[&G]) -> Vec<f64> {
+            vec![0.]
+        }
+    }
+
+    fn population<T: Genome<C>>(count: usize, seed: u64) -> Evolver<C, T, WyRng> {
+        let mut rng = WyRng::seeded(seed);
+        let (mut species, inno_head) = population_init::<C, T>(2, 1, count);
+        for (idx, (genome, _)) in species[0].members.iter_mut().enumerate() {
+            for c in genome.connections_mut() {
+                c.weight = 0.;
+            }
+            genome.connections_mut()[0].weight = idx as f64 + (seed * 100) as f64;
+        }
+        let _ = rng.random::<u64>();
+        Evolver::new((species, inno_head), EvolutionConfig::default(), rng)
+    }
+
+    #[test]
+    fn test_combinations() {
+        assert_eq!(combinations(4, 2).len(), 6);
+        assert_eq!(combinations(3, 2), vec![vec![0, 1], vec![0, 2], vec![1, 2]]);
+        assert_eq!(combinations(2, 0), vec![Vec::<usize>::new()]);
+        assert!(combinations(2, 3).is_empty());
+    }
+
+    test_t!(test_round_robin[T: G]() {
+        let recorder = Recorder(Mutex::new(Vec::new()), 3);
+        let mut coevolver = Coevolver::new(
+            vec![population::<T>(5, 0)],
+            Sampling::RoundRobin,
+            WyRng::seeded(0),
+        )
+        .unwrap();
+        coevolver.eval(&recorder).unwrap();
+
+        // every genome plays each pair of the 4 others
+        let matches = recorder.0.into_inner().unwrap();
+        assert_eq!(matches.len(), 5 * 6);
+        for players in matches {
+            assert_eq!(players.len(), 3);
+            assert!(players[1..].iter().all(|p| *p != players[0]));
+        }
+    });
+
+    test_t!(test_two_populations[T: G]() {
+        let recorder = Recorder(Mutex::new(Vec::new()), 2);
+        let mut coevolver = Coevolver::new(
+            vec![population::<T>(4, 0), population::<T>(3, 1)],
+            Sampling::Random { matches: 2 },
+            WyRng::seeded(0),
+        )
+        .unwrap();
+        coevolver.eval(&recorder).unwrap();
+
+        // genomes only play against the other population
+        let matches = recorder.0.into_inner().unwrap();
+        assert_eq!(matches.len(), (4 + 3) * 2);
+        for players in matches {
+            assert_ne!(players[0] >= 100., players[1] >= 100.);
+        }
+        assert_eq!(coevolver.populations()[1].species().len(), 1);
+    });
+
+    test_t!(test_mean_score[T: G]() {
+        let mut coevolver = Coevolver::new(
+            vec![population::<T>(4, 0)],
+            Sampling::RoundRobin,
+            WyRng::seeded(0),
+        )
+        .unwrap();
+        coevolver.eval(&Heaviest).unwrap();
+
+        // the heaviest wins all 6 of its matches, and the lightest loses all of its own
+        let mut fitness = coevolver.populations()[0]
+            .species()
+            .iter()
+            .flat_map(|Specie { members, .. }| members.iter().map(|(g, f)| (weight(g), *f)))
+            .collect::<Vec<_>>();
+        fitness.sort_by(|(l, _), (r, _)| l.total_cmp(r));
+        assert_eq!(
+            fitness.into_iter().map(|(_, f)| f).collect::<Vec<_>>(),
+            [0., 2. / 6., 4. / 6., 1.]
+        );
+    });
+
+    test_t!(test_eval_miscounted[T: G]() {
+        let mut coevolver = Coevolver::new(
+            vec![population::<T>(4, 0)],
+            Sampling::RoundRobin,
+            WyRng::seeded(0),
+        )
+        .unwrap();
+        assert_eq!(coevolver.eval(&Miscount(0)), Err(EvolutionError::NoPlayers));
+        assert_eq!(
+            coevolver.eval(&Miscount(3)),
+            Err(EvolutionError::Mismatched { wanted: 3, got: 1 })
+        );
+
+        // a single player scored alone is fine, and nobody has been told anything before then
+        assert_eq!(coevolver.populations()[0].ask().len(), 4);
+        coevolver.eval(&Miscount(1)).unwrap();
+        assert!(coevolver.populations()[0].ask().is_empty());
+    });
+
+    test_t!(test_step_failed[T: G]() {
+        // genomes without io have nowhere to form a connection, and so can't be mutated
+        let mut coevolver = Coevolver::new(
+            vec![
+                population::<T>(4, 0),
+                Evolver::new(
+                    population_init::<C, T>(0, 0, 4),
+                    EvolutionConfig::default(),
+                    WyRng::seeded(1),
+                ),
+            ],
+            Sampling::Random { matches: 2 },
+            WyRng::seeded(0),
+        )
+        .unwrap();
+        coevolver.eval(&Heaviest).unwrap();
+
+        let state = |coevolver: &Coevolver<C, T, WyRng>| {
+            let mut state =
+                serde_json::from_str::<serde_json::Value>(&coevolver.to_string().unwrap()).unwrap();
+            for population in state["populations"].as_array_mut().unwrap() {
+                population.as_object_mut().unwrap().remove("rng");
+            }
+            state
+        };
+        let before = state(&coevolver);
+        for _ in 0..2 {
+            assert_eq!(coevolver.step(), Err(EvolutionError::Saturated));
+            assert_eq!(state(&coevolver), before);
+            assert_eq!(coevolver.generation(), 0);
+        }
+    });
+
+    test_t!(test_hall_of_fame[T: G]() {
+        let mut coevolver = Coevolver::new(
+            vec![population::<T>(10, 0)],
+            Sampling::HallOfFame { matches: 3, size: 4 },
+            WyRng::seeded(0),
+        )
+        .unwrap();
+        assert!(Coevolver::new(Vec::<Evolver<C, T, WyRng>>::new(), Sampling::RoundRobin, WyRng::seeded(0)).is_err());
+
+        for generation in 0..6 {
+            coevolver.eval(&Heaviest).unwrap();
+            assert_eq!(coevolver.champions(0).len(), (generation + 1).min(4));
+            coevolver.step().unwrap();
+        }
+
+        let mut resumed =
+            Coevolver::<C, T, WyRng>::from_str(&coevolver.to_string().unwrap()).unwrap();
+        for _ in 0..3 {
+            coevolver.eval(&Heaviest).unwrap();
+            resumed.eval(&Heaviest).unwrap();
+            coevolver.step().unwrap();
+            resumed.step().unwrap();
+        }
+        assert_eq!(coevolver.to_string().unwrap(), resumed.to_string().unwrap());
+    });
+
+    test_t!(test_coevolve[T: G]() {
+        let coevolver = Coevolver::new(
+            vec![population::<T>(20, 0), population::<T>(20, 1)],
+            Sampling::Random { matches: 4 },
+            WyRng::seeded(0),
+        )
+        .unwrap();
+        let parts = coevolve(Heaviest, coevolver, |coevolver| {
+            if coevolver.generation() == 10 {
+                ControlFlow::Break(())
+            } else {
+                ControlFlow::Continue(())
+            }
+        })
+        .unwrap();
+        assert_eq!(parts.len(), 2);
+        assert!(parts.iter().all(|(species, _)| !species.is_empty()));
+    });
+}
@@ -0,0 +1,539 @@
+//! Runtime configuration of the parameters that drive evolution.
+//!
+//! Every field has a default matching what eevee has historically used, and configs are
+//! [serde]-loadable so that they may be written in JSON ( or TOML, or anything else serde
+//! speaks ). Fields missing from a loaded config take their default value.
+
+use crate::{
+    activate::Activation,
+    random::{percent, ConnectionEvent, EventKind, GenomeEvent},
+};
+use core::error::Error;
+use serde::{Deserialize, Serialize};
+use std::{fs, path::Path};
+
+/// Probabilities of mutations happening to a genome, its nodes, and its connections. Every
+/// probability is a whole percent in `0..=100`, and is turned into an rng threshold with
+/// [percent].
+///
+/// Node and activation mutations are opt-in, and never happen by default. The default genome
+/// mutations already add up to 100, so enabling them means making room among the others, as in
+/// ```
+/// # use eevee::config::MutationConfig;
+/// let config = MutationConfig {
+///     mutate_connection: 70,
+///     mutate_node: 7,
+///     mutate_activation: 3,
+///     ..MutationConfig::default()
+/// };
+/// ```
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MutationConfig {
+    /// Chance of a genome mutation forming a new connection
+    pub new_connection: u64,
+    /// Chance of a genome mutation bisecting an existing connection
+    pub bisect_connection: u64,
+    /// Chance of a genome mutation mutating its connections
+    pub mutate_connection: u64,
+    /// Chance of a genome mutation mutating its nodes, 0 unless opted into
+    pub mutate_node: u64,
+    /// Chance of a genome mutation changing the activation of its nodes, 0 unless opted into
+    pub mutate_activation: u64,
+    /// Chance of each connection being mutated when a genome mutates its connections
+    pub connection_rate: u64,
+    /// Chance of each node being mutated when a genome mutates its nodes or their activations
+    pub node_rate: u64,
+    /// Chance of a connection mutation disabling the connection
+    pub connection_disable: u64,
+    /// Chance of a connection mutation mutating one of its params
+    pub connection_param: u64,
+    /// Chance of a param mutation replacing the param outright, rather than perturbing it
+    pub param_replace: u64,
+    /// Scale of a param perturbation, relative to a replacement
+    pub param_perturb: f64,
+    /// Activation of nodes grown through bisection
+    pub activation: Activation,
+}
+
+impl Default for MutationConfig {
+    fn default() -> Self {
+        Self {
+            new_connection: 5,
+            bisect_connection: 15,
+            mutate_connection: 80,
+            mutate_node: 0,
+            mutate_activation: 0,
+            connection_rate: 20,
+            node_rate: 20,
+            connection_disable: 1,
+            connection_param: 99,
+            param_replace: 10,
+            param_perturb: 0.05,
+            activation: Activation::default(),
+        }
+    }
+}
+
+impl MutationConfig {
+    /// Thresholds for picking a [GenomeEvent]
+    pub fn genome_probabilities(&self) -> [u64; GenomeEvent::COUNT] {
+        [
+            percent(self.new_connection),
+            percent(self.bisect_connection),
+            percent(self.mutate_connection),
+            percent(self.mutate_node),
+            percent(self.mutate_activation),
+        ]
+    }
+
+    /// Thresholds for picking a [ConnectionEvent]
+    pub fn connection_probabilities(&self) -> [u64; ConnectionEvent::COUNT] {
+        [
+            percent(self.connection_disable),
+            percent(self.connection_param),
+        ]
+    }
+
+    /// Fails if any probability is more than 100 percent
+    pub fn validate(&self) -> Result<(), Box<dyn Error>> {
+        for (name, p) in [
+            ("new_connection", self.new_connection),
+            ("bisect_connection", self.bisect_connection),
+            ("mutate_connection", self.mutate_connection),
+            ("mutate_node", self.mutate_node),
+            ("mutate_activation", self.mutate_activation),
+            ("connection_rate", self.connection_rate),
+            ("node_rate", self.node_rate),
+            ("connection_disable", self.connection_disable),
+            ("connection_param", self.connection_param),
+            ("param_replace", self.param_replace),
+        ] {
+            if p > 100 {
+                return Err(format!("{name} is {p} percent, but may be at most 100").into());
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Parameters of novelty search, used when evolving by a
+/// [BehaviorScenario](crate::scenario::BehaviorScenario).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct NoveltyConfig {
+    /// Number of nearest neighbors whose mean distance is the sparseness of a behavior
+    pub k: usize,
+    /// Sparseness above which a behavior is added to the archive
+    pub archive_threshold: f64,
+    /// Most behaviors that the archive may hold, the oldest being forgotten first
+    pub archive_limit: usize,
+    /// Portion of fitness replaced by novelty, where 0 is pure fitness and 1 is pure novelty
+    pub blend: f64,
+}
+
+impl Default for NoveltyConfig {
+    fn default() -> Self {
+        Self {
+            k: 15,
+            archive_threshold: 1.,
+            archive_limit: 1000,
+            blend: 0.5,
+        }
+    }
+}
+
+/// Parameters of the [HallOfFame](crate::hall::HallOfFame) kept while evolving
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HallOfFameConfig {
+    /// Most genomes that the hall of fame may hold, where 0 keeps none
+    pub size: usize,
+    /// Number of the fittest inductees copied into each new generation, replacing some of its
+    /// offspring
+    pub reinject: usize,
+}
+
+impl Default for HallOfFameConfig {
+    fn default() -> Self {
+        Self {
+            size: 10,
+            reinject: 0,
+        }
+    }
+}
+
+/// A controller of the compatibility threshold, nudging it each generation so that the
+/// number of species stays near some target
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ThresholdControl {
+    /// Number of species to aim for
+    pub target: usize,
+    /// Amount the threshold is raised by when there are too many species, or lowered by when
+    /// there are too few
+    pub step: f64,
+    /// Least that the threshold may be lowered to
+    pub min: f64,
+}
+
+impl Default for ThresholdControl {
+    fn default() -> Self {
+        Self {
+            target: 10,
+            step: 0.3,
+            min: 0.1,
+        }
+    }
+}
+
+/// How genomes are partitioned into species, each strategy being a
+/// [Speciation](crate::population::Speciation) of the same name
+#[derive(Debug, Default, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum SpeciationStrategy {
+    /// Join the first specie whose founder is close enough
+    #[default]
+    FirstMatch,
+    /// Join the first specie whose repr is close enough, with reprs picked at random from the
+    /// members of each generation
+    RandomRepr,
+    /// Cluster into `k` species by k-medoids
+    KMedoids { k: usize, iterations: usize },
+    /// Like FirstMatch, but matched in parallel when compiled with `--features parallel`
+    Parallel,
+}
+
+/// How the parents of each child are picked, each strategy being a
+/// [Selection](crate::reproduce::Selection) of the same name
+#[derive(Debug, Default, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum SelectionStrategy {
+    /// Cycle through members and pairs of members, fittest first
+    #[default]
+    Ordered,
+    /// The fittest of `size` members picked at random
+    Tournament { size: usize },
+    /// Proportional to fitness
+    Roulette,
+    /// Proportional to rank by fitness
+    Rank,
+    /// Any of the fittest `survival` portion of members
+    Truncation { survival: f64 },
+}
+
+/// What becomes of each fitness that isn't finite ( NaN or either infinity ) before a
+/// generation is speciated. Objectives of a multi-objective generation are each treated the
+/// same way, and a genome is discarded if any of its objectives would be.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum FitnessPolicy {
+    /// Clamp to the least or greatest finite fitness of the generation, NaN being the least.
+    /// If none are finite, every fitness is 0.
+    #[default]
+    Clamp,
+    /// Discard the genome, so that it never reproduces
+    Discard,
+    /// Replace with a fixed fitness
+    Penalize { fitness: f64 },
+}
+
+impl FitnessPolicy {
+    /// Apply this policy to each fitness of `fitted`, returning what's left along with how
+    /// many weren't finite
+    pub fn apply<T>(&self, fitted: Vec<(T, f64)>) -> (Vec<(T, f64)>, usize) {
+        let (fitted, non_finite) = self.apply_multi(
+            fitted
+                .into_iter()
+                .map(|(item, fitness)| (item, [fitness]))
+                .collect(),
+        );
+        (
+            fitted
+                .into_iter()
+                .map(|(item, [fitness])| (item, fitness))
+                .collect(),
+            non_finite,
+        )
+    }
+
+    /// Like [apply](FitnessPolicy::apply), but for several objectives, each clamped by the
+    /// bounds of its own. Returns how many genomes had any objective that wasn't finite.
+    pub fn apply_multi<T, O: AsRef<[f64]> + AsMut<[f64]>>(
+        &self,
+        fitted: Vec<(T, O)>,
+    ) -> (Vec<(T, O)>, usize) {
+        let non_finite = fitted
+            .iter()
+            .filter(|(_, o)| o.as_ref().iter().any(|fit| !fit.is_finite()))
+            .count();
+        if non_finite == 0 {
+            return (fitted, 0);
+        }
+
+        let fitted = match *self {
+            Self::Discard => fitted
+                .into_iter()
+                .filter(|(_, o)| o.as_ref().iter().all(|fit| fit.is_finite()))
+                .collect(),
+            Self::Penalize { fitness } => fitted
+                .into_iter()
+                .map(|(item, mut o)| {
+                    for fit in o.as_mut().iter_mut().filter(|fit| !fit.is_finite()) {
+                        *fit = fitness;
+                    }
+                    (item, o)
+                })
+                .collect(),
+            Self::Clamp => {
+                let count = fitted.first().map_or(0, |(_, o)| o.as_ref().len());
+                let bounds =
+                    (0..count)
+                        .map(|idx| {
+                            fitted
+                                .iter()
+                                .map(|(_, o)| o.as_ref()[idx])
+                                .filter(|fit| fit.is_finite())
+                                .fold(None, |acc: Option<(f64, f64)>, fit| {
+                                    Some(acc.map_or((fit, fit), |(min, max)| {
+                                        (min.min(fit), max.max(fit))
+                                    }))
+                                })
+                                .unwrap_or((0., 0.))
+                        })
+                        .collect::<Vec<_>>();
+                fitted
+                    .into_iter()
+                    .map(|(item, mut o)| {
+                        for (fit, (min, max)) in o.as_mut().iter_mut().zip(&bounds) {
+                            if *fit == f64::INFINITY {
+                                *fit = *max;
+                            } else if !fit.is_finite() {
+                                *fit = *min;
+                            }
+                        }
+                        (item, o)
+                    })
+                    .collect()
+            }
+        };
+
+        (fitted, non_finite)
+    }
+}
+
+/// Which islands the migrants of each island go to
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Topology {
+    /// Each island sends migrants to the next one, and the last to the first
+    #[default]
+    Ring,
+    /// Each island sends migrants to every other
+    Full,
+}
+
+/// Parameters of migration between [Islands](crate::islands::Islands)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MigrationConfig {
+    /// Generations between each migration, where 0 never migrates
+    pub interval: usize,
+    /// Number of the fittest genomes of each island who migrate to each of its neighbors
+    pub count: usize,
+    pub topology: Topology,
+}
+
+impl Default for MigrationConfig {
+    fn default() -> Self {
+        Self {
+            interval: 10,
+            count: 2,
+            topology: Topology::default(),
+        }
+    }
+}
+
+/// Parameters of an evolution run, threaded through mutation, speciation, and reproduction.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EvolutionConfig {
+    pub mutation: MutationConfig,
+    pub novelty: NoveltyConfig,
+    pub hall_of_fame: HallOfFameConfig,
+    /// Compatibility delta under which a genome is considered a member of some specie
+    pub specie_threshold: f64,
+    /// If set, the threshold starts at [specie_threshold](EvolutionConfig::specie_threshold)
+    /// and is adjusted every generation by this controller
+    pub threshold_control: Option<ThresholdControl>,
+    pub speciation: SpeciationStrategy,
+    /// Generations that a specie may go without improving before it's truncated to its fittest
+    /// couple of members
+    pub stagnation_limit: usize,
+    /// Portion of a specie's offspring ( excluding its elites ) produced by crossover, the
+    /// rest being mutated copies. Clamped into `0..=1`.
+    pub crossover_rate: f64,
+    /// Number of the fittest members of each specie who are kept as-is in the next generation
+    pub elitism: usize,
+    pub selection: SelectionStrategy,
+    pub fitness_policy: FitnessPolicy,
+}
+
+impl Default for EvolutionConfig {
+    fn default() -> Self {
+        Self {
+            mutation: MutationConfig::default(),
+            novelty: NoveltyConfig::default(),
+            hall_of_fame: HallOfFameConfig::default(),
+            specie_threshold: 4.,
+            threshold_control: None,
+            speciation: SpeciationStrategy::default(),
+            stagnation_limit: 10,
+            crossover_rate: 0.75,
+            elitism: 1,
+            selection: SelectionStrategy::default(),
+            fitness_policy: FitnessPolicy::default(),
+        }
+    }
+}
+
+impl EvolutionConfig {
+    /// Serialize this config to a JSON string
+    pub fn to_string(&self) -> Result<String, Box<dyn Error>> {
+        Ok(serde_json::to_string(self)?)
+    }
+
+    /// Deserialize a config from a JSON string. Fails if its
+    /// [mutation](MutationConfig::validate) isn't valid.
+    #[allow(clippy::should_implement_trait)]
+    pub fn from_str(s: &str) -> Result<Self, Box<dyn Error>> {
+        let config = serde_json::from_str::<Self>(s)?;
+        config.mutation.validate()?;
+        Ok(config)
+    }
+
+    pub fn to_file<P: AsRef<Path>>(&self, path: P) -> Result<(), Box<dyn Error>> {
+        fs::write(path, self.to_string()?)?;
+        Ok(())
+    }
+
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self, Box<dyn Error>> {
+        Self::from_str(&fs::read_to_string(path)?)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_default_probabilities() {
+        let config = MutationConfig::default();
+        assert_eq!(
+            config.genome_probabilities(),
+            [percent(5), percent(15), percent(80), percent(0), percent(0)]
+        );
+        assert_eq!(config.connection_probabilities(), [percent(1), percent(99)]);
+    }
+
+    #[test]
+    fn test_config_partial() {
+        let config = EvolutionConfig::from_str(
+            r#"{"specie_threshold": 2.5, "mutation": {"new_connection": 10}}"#,
+        )
+        .unwrap();
+
+        assert_eq!(config.specie_threshold, 2.5);
+        assert_eq!(config.mutation.new_connection, 10);
+        assert_eq!(
+            config.mutation.bisect_connection,
+            MutationConfig::default().bisect_connection
+        );
+        assert_eq!(
+            config.stagnation_limit,
+            EvolutionConfig::default().stagnation_limit
+        );
+    }
+
+    #[test]
+    fn test_config_percent_bounds() {
+        assert!(EvolutionConfig::from_str(r#"{"mutation": {"connection_rate": 100}}"#).is_ok());
+        let err = EvolutionConfig::from_str(r#"{"mutation": {"connection_rate": 101}}"#)
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("connection_rate"));
+
+        // configs made in code aren't validated, and so percents saturate
+        let config = MutationConfig {
+            connection_disable: 1000,
+            ..MutationConfig::default()
+        };
+        assert!(config.validate().is_err());
+        assert_eq!(config.connection_probabilities()[0], percent(100));
+    }
+
+    #[test]
+    fn test_fitness_policy() {
+        let fitted = vec![
+            ('a', 1.),
+            ('b', f64::NAN),
+            ('c', f64::INFINITY),
+            ('d', -2.),
+            ('e', f64::NEG_INFINITY),
+        ];
+
+        let (clamped, non_finite) = FitnessPolicy::Clamp.apply(fitted.clone());
+        assert_eq!(non_finite, 3);
+        assert_eq!(
+            clamped,
+            [('a', 1.), ('b', -2.), ('c', 1.), ('d', -2.), ('e', -2.)]
+        );
+
+        let (discarded, non_finite) = FitnessPolicy::Discard.apply(fitted.clone());
+        assert_eq!(non_finite, 3);
+        assert_eq!(discarded, [('a', 1.), ('d', -2.)]);
+
+        let (penalized, non_finite) =
+            FitnessPolicy::Penalize { fitness: -10. }.apply(fitted.clone());
+        assert_eq!(non_finite, 3);
+        assert_eq!(
+            penalized,
+            [('a', 1.), ('b', -10.), ('c', -10.), ('d', -2.), ('e', -10.)]
+        );
+
+        let (clamped, non_finite) = FitnessPolicy::Clamp.apply(vec![('a', f64::NAN)]);
+        assert_eq!(non_finite, 1);
+        assert_eq!(clamped, [('a', 0.)]);
+    }
+
+    #[test]
+    fn test_fitness_policy_multi() {
+        let fitted = vec![
+            ('a', vec![1., f64::NAN]),
+            ('b', vec![3., 5.]),
+            ('c', vec![f64::INFINITY, 4.]),
+        ];
+
+        let (clamped, non_finite) = FitnessPolicy::Clamp.apply_multi(fitted.clone());
+        assert_eq!(non_finite, 2);
+        assert_eq!(
+            clamped,
+            [
+                ('a', vec![1., 4.]),
+                ('b', vec![3., 5.]),
+                ('c', vec![3., 4.])
+            ]
+        );
+
+        let (discarded, _) = FitnessPolicy::Discard.apply_multi(fitted);
+        assert_eq!(discarded, [('b', vec![3., 5.])]);
+    }
+
+    #[test]
+    fn test_config_roundtrip() {
+        let config = EvolutionConfig {
+            crossover_rate: 0.5,
+            ..EvolutionConfig::default()
+        };
+        assert_eq!(
+            config,
+            EvolutionConfig::from_str(&config.to_string().unwrap()).unwrap()
+        );
+    }
+}
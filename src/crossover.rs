@@ -1,7 +1,7 @@
 //! Functions related to performing measuring compatability for and performing crossover
 //! reproduction.
 
-use crate::genome::Connection;
+use crate::genome::{Connection, Genome};
 use core::cmp::Ordering;
 use rand::RngCore;
 
@@ -117,6 +117,8 @@ pub fn avg_param_diff<C: Connection>(l: &[C], r: &[C]) -> f64 {
 
 /// difference between [Connection]s in terms of crossover compatability. Higher deltas tend to
 /// yield more destructive crossover.
+/// Nodes are deliberately left out, as they are laid out by index rather than innovation, and
+/// so aren't homologous between genomes the way connections are.
 pub fn delta<C: Connection>(l: &[C], r: &[C]) -> f64 {
     let l_size = l.len() as f64;
     let r_size = r.len() as f64;
@@ -250,6 +252,33 @@ pub fn crossover<C: Connection>(
     usort
 }
 
+/// The first `size` nodes of a crossover child of `l` and `r`, where `l_fit` is the fitness of
+/// l compared to r. Nodes that both parents have are taken from the fitter parent, or from
+/// either at random when equally fit. Nodes that only one parent has are taken from it.
+pub fn crossover_nodes<C: Connection, G: Genome<C>>(
+    l: &G,
+    r: &G,
+    l_fit: Ordering,
+    size: usize,
+    rng: &mut impl RngCore,
+) -> Vec<G::Node> {
+    (0..size)
+        .map(|idx| {
+            match (l.nodes().get(idx), r.nodes().get(idx)) {
+                (Some(l_node), Some(r_node)) => match l_fit {
+                    Ordering::Greater => l_node,
+                    Ordering::Less => r_node,
+                    Ordering::Equal if rng.next_u64() < C::PROBABILITY_PICK_RL => r_node,
+                    Ordering::Equal => l_node,
+                },
+                (Some(node), None) | (None, Some(node)) => node,
+                (None, None) => return G::Node::default(),
+            }
+            .clone()
+        })
+        .collect()
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
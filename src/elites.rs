@@ -0,0 +1,642 @@
+//! Quality-diversity search with MAP-Elites, where rather than evolving a population towards a
+//! single champion, the fittest genome of every niche of some behavior space is kept.
+//!
+//! Niches are the cells of a [Grid] over the behavior of a genome, as evaluated by a
+//! [BehaviorScenario]. Each generation, parents are picked from the elites at random, and their
+//! children replace the elite of whichever niche they land in if they're fitter.
+//!
+//! Implementation based on illuminating search spaces by mapping elites (mouret and clune 2015)
+
+use crate::{
+    config::EvolutionConfig,
+    error::EvolutionError,
+    genome::{Connection, Genome, InnoGen},
+    population::Specie,
+    scenario::BehaviorScenario,
+};
+use core::{cmp::Ordering, error::Error, marker::PhantomData, mem::take, ops::ControlFlow};
+use rand::{Rng, RngCore};
+#[cfg(feature = "parallel")]
+use rayon::{
+    iter::{IntoParallelIterator, ParallelIterator},
+    ThreadPool, ThreadPoolBuilder,
+};
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, fmt::Write, fs, path::Path};
+
+/// A grid over behavior space, where each dimension of a behavior is split evenly into some
+/// number of bins between its bounds. Behaviors outside of the bounds fall into the outermost
+/// bins.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Grid {
+    /// (lower, upper) bound of each dimension
+    bounds: Vec<(f64, f64)>,
+    /// number of bins in each dimension
+    bins: Vec<usize>,
+}
+
+impl Grid {
+    /// A grid of dimensions described by their ((lower, upper), bins). Fails if any dimension
+    /// has no bins.
+    pub fn new(
+        dimensions: impl IntoIterator<Item = ((f64, f64), usize)>,
+    ) -> Result<Self, Box<dyn Error>> {
+        let (bounds, bins): (Vec<_>, Vec<_>) = dimensions.into_iter().unzip();
+        if let Some(dimension) = bins.iter().position(|bins| *bins == 0) {
+            return Err(format!("dimension {dimension} of grid has no bins").into());
+        }
+
+        Ok(Self { bounds, bins })
+    }
+
+    /// Number of dimensions, and so the length of each behavior
+    pub fn dimensions(&self) -> usize {
+        self.bins.len()
+    }
+
+    /// Total number of cells
+    pub fn cells(&self) -> usize {
+        self.bins.iter().product()
+    }
+
+    /// The cell that a behavior falls into, or nothing if it doesn't have one value for each
+    /// dimension, or any of those values isn't a number
+    pub fn cell(&self, behavior: &[f64]) -> Option<usize> {
+        if behavior.len() != self.dimensions() || behavior.iter().any(|v| v.is_nan()) {
+            return None;
+        }
+
+        Some(
+            behavior
+                .iter()
+                .zip(self.bounds.iter().zip(&self.bins))
+                .fold(0, |cell, (v, ((lo, hi), bins))| {
+                    let bin = ((v - lo) / (hi - lo) * *bins as f64).floor();
+                    cell * bins + (bin.max(0.) as usize).min(bins - 1)
+                }),
+        )
+    }
+}
+
+/// The fittest genome found in some cell, with the fitness and behavior that put it there
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Elite<G> {
+    pub genome: G,
+    pub fitness: f64,
+    pub behavior: Vec<f64>,
+}
+
+/// A map of the elites of each cell of a [Grid]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Elites<G> {
+    grid: Grid,
+    elites: BTreeMap<usize, Elite<G>>,
+}
+
+impl<G> Elites<G> {
+    pub fn new(grid: Grid) -> Self {
+        Self {
+            grid,
+            elites: BTreeMap::new(),
+        }
+    }
+
+    pub fn grid(&self) -> &Grid {
+        &self.grid
+    }
+
+    /// Number of cells who have an elite
+    pub fn len(&self) -> usize {
+        self.elites.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.elites.is_empty()
+    }
+
+    /// Portion of cells who have an elite
+    pub fn coverage(&self) -> f64 {
+        self.len() as f64 / self.grid.cells() as f64
+    }
+
+    pub fn get(&self, cell: usize) -> Option<&Elite<G>> {
+        self.elites.get(&cell)
+    }
+
+    /// Every elite along with its cell, ordered by cell
+    pub fn iter(&self) -> impl Iterator<Item = (usize, &Elite<G>)> {
+        self.elites.iter().map(|(cell, elite)| (*cell, elite))
+    }
+
+    /// The fittest of every elite
+    pub fn fittest(&self) -> Option<&Elite<G>> {
+        self.elites
+            .values()
+            .max_by(|l, r| l.fitness.total_cmp(&r.fitness))
+    }
+
+    /// Insert a genome into the cell of its behavior if it's empty, or if the genome is fitter
+    /// than its elite. Returns whether the genome was inserted.
+    pub fn insert(&mut self, genome: G, fitness: f64, behavior: Vec<f64>) -> bool {
+        let Some(cell) = self.grid.cell(&behavior) else {
+            return false;
+        };
+
+        if fitness.is_nan() || self.get(cell).is_some_and(|e| e.fitness >= fitness) {
+            return false;
+        }
+
+        self.elites.insert(
+            cell,
+            Elite {
+                genome,
+                fitness,
+                behavior,
+            },
+        );
+        true
+    }
+
+    /// Save every elite to a file named by its cell inside of a directory at `path`, along with
+    /// an `elites.csv` describing the cell, fitness, and behavior of each of them
+    pub fn to_files<C: Connection, P: AsRef<Path>>(&self, path: P) -> Result<(), Box<dyn Error>>
+    where
+        G: Genome<C>,
+    {
+        let mut table =
+            (0..self.grid.dimensions()).fold(String::from("cell,fitness"), |mut acc, dimension| {
+                let _ = write!(acc, ",behavior_{dimension}");
+                acc
+            });
+        table.push('\n');
+
+        for (cell, elite) in self.iter() {
+            elite
+                .genome
+                .to_file(path.as_ref().join(format!("{cell}.json")))?;
+            write!(table, "{cell},{}", elite.fitness)?;
+            for v in elite.behavior.iter() {
+                write!(table, ",{v}")?;
+            }
+            table.push('\n');
+        }
+
+        fs::write(path.as_ref().join("elites.csv"), table)?;
+        Ok(())
+    }
+}
+
+/// A stepwise handle on a MAP-Elites search, in the same way that an
+/// [Evolver](crate::scenario::Evolver) is for evolution. Each generation goes like
+/// - [ask](MapElites::ask) for the genomes who need to be evaluated
+/// - [tell](MapElites::tell) their fitness and behavior, in the same order, which inserts them
+/// - [step](MapElites::step) to produce the next batch of children from the elites
+///
+/// If the genomes can be evaluated by a [BehaviorScenario], [eval](MapElites::eval) does the
+/// first 2. Like an evolver, a MapElites whose rng is serializable is a checkpoint of itself.
+#[derive(Serialize, Deserialize)]
+#[serde(bound(serialize = "R: Serialize", deserialize = "R: Deserialize<'de>"))]
+pub struct MapElites<C: Connection, G: Genome<C>, R: RngCore> {
+    elites: Elites<G>,
+    pending: Vec<G>,
+    batch: usize,
+    inno_head: usize,
+    rng: R,
+    config: EvolutionConfig,
+    generation: usize,
+    #[serde(skip)]
+    connection: PhantomData<C>,
+    #[cfg(feature = "parallel")]
+    #[serde(skip, default = "thread_pool")]
+    thread_pool: ThreadPool,
+}
+
+#[cfg(feature = "parallel")]
+fn thread_pool() -> ThreadPool {
+    ThreadPoolBuilder::new().build().unwrap()
+}
+
+impl<C: Connection, G: Genome<C>, R: RngCore> MapElites<C, G, R> {
+    /// Start searching from some initial population, like the one returned from
+    /// [population_init](crate::population::population_init), producing `batch` children
+    /// each generation after the first.
+    pub fn new(
+        (species, inno_head): (Vec<Specie<C, G>>, usize),
+        grid: Grid,
+        batch: usize,
+        config: EvolutionConfig,
+        rng: R,
+    ) -> Self {
+        Self {
+            elites: Elites::new(grid),
+            pending: species
+                .into_iter()
+                .flat_map(|Specie { members, .. }| members.into_iter().map(|(genome, _)| genome))
+                .collect(),
+            batch,
+            inno_head,
+            rng,
+            config,
+            generation: 0,
+            connection: PhantomData,
+            #[cfg(feature = "parallel")]
+            thread_pool: thread_pool(),
+        }
+    }
+
+    /// Genomes of the current generation who are waiting to be evaluated
+    pub fn ask(&self) -> &[G] {
+        &self.pending
+    }
+
+    /// Provide a fitness and behavior for each genome returned by [ask](MapElites::ask), in
+    /// the same order. Each genome is inserted into the elites if it beats the elite of its
+    /// cell.
+    pub fn tell(
+        &mut self,
+        evaluations: impl IntoIterator<Item = (f64, Vec<f64>)>,
+    ) -> Result<(), EvolutionError> {
+        let evaluations = evaluations.into_iter().collect::<Vec<_>>();
+        if evaluations.len() != self.pending.len() {
+            return Err(EvolutionError::Mismatched {
+                wanted: self.pending.len(),
+                got: evaluations.len(),
+            });
+        }
+
+        for (genome, (fitness, behavior)) in take(&mut self.pending).into_iter().zip(evaluations) {
+            self.elites.insert(genome, fitness, behavior);
+        }
+        Ok(())
+    }
+
+    /// Produce the next batch of children, each a mutated copy or crossover child of elites
+    /// picked at random, split by [crossover_rate](EvolutionConfig::crossover_rate). Fails if
+    /// the current generation hasn't been evaluated yet, if there are no elites, or if a child
+    /// can't be mutated.
+    pub fn step(&mut self) -> Result<(), EvolutionError> {
+        if !self.pending.is_empty() {
+            return Err(EvolutionError::Unevaluated {
+                pending: self.pending.len(),
+                generation: self.generation,
+            });
+        }
+
+        if self.elites.is_empty() {
+            return Err(EvolutionError::TooFewMembers {
+                produce: self.batch,
+                members: 0,
+            });
+        }
+
+        let mut innogen = InnoGen::new(self.inno_head);
+        let elite = |idx| self.elites.elites.values().nth(idx).unwrap();
+
+        self.pending = (0..self.batch)
+            .map(|_| {
+                let l_idx = self.rng.random_range(0..self.elites.len());
+                let l = elite(l_idx);
+                let mut child = if self.elites.len() > 1
+                    && self
+                        .rng
+                        .random_bool(self.config.crossover_rate.clamp(0., 1.))
+                {
+                    // any other elite, so that one is never crossed with itself
+                    let r_idx = self.rng.random_range(0..self.elites.len() - 1);
+                    let r = elite(if r_idx >= l_idx { r_idx + 1 } else { r_idx });
+                    let fitness_cmp = l.fitness.partial_cmp(&r.fitness).unwrap_or(Ordering::Equal);
+                    l.genome
+                        .reproduce_with(&r.genome, fitness_cmp, &mut self.rng)
+                } else {
+                    l.genome.clone()
+                };
+
+                child.mutate(&mut self.rng, &mut innogen, &self.config.mutation)?;
+                Ok(child)
+            })
+            .collect::<Result<_, EvolutionError>>()?;
+
+        self.inno_head = innogen.head;
+        self.generation += 1;
+        Ok(())
+    }
+
+    pub fn elites(&self) -> &Elites<G> {
+        &self.elites
+    }
+
+    pub fn generation(&self) -> usize {
+        self.generation
+    }
+
+    pub fn inno_head(&self) -> usize {
+        self.inno_head
+    }
+
+    /// Stop searching, and take the elites
+    pub fn into_elites(self) -> Elites<G> {
+        self.elites
+    }
+}
+
+impl<
+        C: Connection,
+        #[cfg(not(feature = "parallel"))] G: Genome<C>,
+        #[cfg(feature = "parallel")] G: Genome<C> + Send,
+        R: RngCore,
+    > MapElites<C, G, R>
+{
+    /// Evaluate every genome from [ask](MapElites::ask) with `scenario`, and
+    /// [tell](MapElites::tell) their fitness and behavior. If compiled with
+    /// `--features parallel`, this is done in a thread-pool of one thread per cpu on the host.
+    pub fn eval<
+        #[cfg(not(feature = "parallel"))] S: BehaviorScenario<C, G>,
+        #[cfg(feature = "parallel")] S: BehaviorScenario<C, G> + Sync,
+    >(
+        &mut self,
+        scenario: &S,
+    ) {
+        let pending = take(&mut self.pending);
+
+        #[cfg(not(feature = "parallel"))]
+        let evaluated = pending
+            .into_iter()
+            .map(|genome| {
+                let (fitness, behavior) = scenario.eval(&genome);
+                (genome, fitness, behavior)
+            })
+            .collect::<Vec<_>>();
+        #[cfg(feature = "parallel")]
+        let evaluated = self.thread_pool.install(|| {
+            pending
+                .into_par_iter()
+                .map(|genome| {
+                    let (fitness, behavior) = scenario.eval(&genome);
+                    (genome, fitness, behavior)
+                })
+                .collect::<Vec<_>>()
+        });
+
+        for (genome, fitness, behavior) in evaluated {
+            self.elites.insert(genome, fitness, behavior);
+        }
+    }
+}
+
+impl<C: Connection, G: Genome<C>, R: RngCore + Serialize + for<'de> Deserialize<'de>>
+    MapElites<C, G, R>
+{
+    /// Serialize this search to a JSON string
+    pub fn to_string(&self) -> Result<String, Box<dyn Error>> {
+        Ok(serde_json::to_string(self)?)
+    }
+
+    /// Deserialize a search from a JSON string
+    #[allow(clippy::should_implement_trait)]
+    pub fn from_str(s: &str) -> Result<Self, Box<dyn Error>> {
+        serde_json::from_str(s).map_err(|op| op.into())
+    }
+
+    /// Checkpoint the complete search state to a file at `path`
+    pub fn to_file<P: AsRef<Path>>(&self, path: P) -> Result<(), Box<dyn Error>> {
+        fs::write(path, self.to_string()?)?;
+        Ok(())
+    }
+
+    /// Resume searching from a checkpoint written by [to_file](MapElites::to_file)
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self, Box<dyn Error>> {
+        Self::from_str(&fs::read_to_string(path)?)
+    }
+}
+
+/// The MAP-Elites counterpart to [evolve](crate::scenario::evolve), searching the cells of
+/// `grid` for elites in batches of `batch` children. `hook` is called after each generation is
+/// evaluated, and may halt the search by returning a ControlFlow::Break. Fails if no genome
+/// of some generation becomes an elite, or if a child can't be mutated.
+///
+/// If compiled with `--features parallel`, evaluation will run in a thread-pool of one thread
+/// per cpu on the host.
+pub fn illuminate<
+    C: Connection,
+    #[cfg(not(feature = "parallel"))] G: Genome<C>,
+    #[cfg(feature = "parallel")] G: Genome<C> + Send,
+    I: FnOnce((usize, usize)) -> (Vec<Specie<C, G>>, usize),
+    #[cfg(not(feature = "parallel"))] S: BehaviorScenario<C, G>,
+    #[cfg(feature = "parallel")] S: BehaviorScenario<C, G> + Sync,
+    R: RngCore,
+>(
+    scenario: S,
+    init: I,
+    grid: Grid,
+    batch: usize,
+    config: EvolutionConfig,
+    rng: R,
+    mut hook: impl FnMut(&MapElites<C, G, R>) -> ControlFlow<()>,
+) -> Result<Elites<G>, EvolutionError> {
+    let mut search = MapElites::new(init(scenario.io()), grid, batch, config, rng);
+    loop {
+        search.eval(&scenario);
+        if hook(&search).is_break() {
+            break Ok(search.into_elites());
+        }
+
+        search.step()?;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        config::MutationConfig,
+        genome::{Recurrent, WConnection},
+        macros::fixtures::WeightShape,
+        population::population_init,
+        random::WyRng,
+        test_t,
+    };
+
+    type C = WConnection;
+    type G = Recurrent<C>;
+
+    fn grid() -> Grid {
+        Grid::new([((0., 10.), 5), ((4., 9.), 5)]).unwrap()
+    }
+
+    #[test]
+    fn test_grid_cell() {
+        let grid = grid();
+        assert_eq!(grid.cells(), 25);
+        assert_eq!(grid.cell(&[0., 4.]), Some(0));
+        assert_eq!(grid.cell(&[0., 5.]), Some(1));
+        assert_eq!(grid.cell(&[2., 4.]), Some(5));
+        assert_eq!(grid.cell(&[9.9, 8.9]), Some(24));
+        assert_eq!(grid.cell(&[100., 100.]), Some(24));
+        assert_eq!(grid.cell(&[-100., -100.]), Some(0));
+        assert_eq!(grid.cell(&[0.]), None);
+        assert_eq!(grid.cell(&[f64::NAN, 0.]), None);
+
+        assert!(Grid::new([((0., 10.), 5), ((4., 9.), 0)]).is_err());
+    }
+
+    #[test]
+    fn test_insert() {
+        let mut elites = Elites::new(grid());
+        assert!(elites.insert("a", 1., vec![0., 4.]));
+        assert!(!elites.insert("b", 0.5, vec![0.5, 4.5]));
+        assert!(!elites.insert("c", f64::NAN, vec![0.5, 4.5]));
+        assert!(elites.insert("d", 2., vec![0.5, 4.5]));
+        assert!(elites.insert("e", 0., vec![5., 5.]));
+        assert!(!elites.insert("f", 3., vec![]));
+
+        assert_eq!(elites.len(), 2);
+        assert_eq!(elites.get(0).unwrap().genome, "d");
+        assert_eq!(elites.fittest().unwrap().genome, "d");
+        assert_eq!(elites.coverage(), 2. / 25.);
+    }
+
+    test_t!(test_illuminate[T: G]() {
+        let elites = illuminate(
+            WeightShape,
+            |(sensory, action)| population_init::<C, T>(sensory, action, 10),
+            grid(),
+            20,
+            EvolutionConfig::default(),
+            WyRng::seeded(0xeeee),
+            |search| {
+                if search.generation() == 30 {
+                    ControlFlow::Break(())
+                } else {
+                    ControlFlow::Continue(())
+                }
+            },
+        )
+        .unwrap();
+
+        assert!(elites.len() > 1);
+        for (cell, elite) in elites.iter() {
+            assert_eq!(elites.grid().cell(&elite.behavior), Some(cell));
+            assert_eq!(WeightShape.eval(&elite.genome), (elite.fitness, elite.behavior.clone()));
+        }
+    });
+
+    test_t!(test_map_elites_no_elites[T: G]() {
+        let mut search = MapElites::new(
+            population_init::<C, T>(2, 1, 10),
+            grid(),
+            10,
+            EvolutionConfig::default(),
+            WyRng::seeded(0xeeee),
+        );
+        // behaviors without a cell are never elites
+        search.tell(vec![(1., vec![0.]); 10]).unwrap();
+        assert!(search.elites().is_empty());
+        assert_eq!(
+            search.step(),
+            Err(EvolutionError::TooFewMembers {
+                produce: 10,
+                members: 0
+            })
+        );
+    });
+
+    test_t!(test_map_elites_crossover_distinct[T: G]() {
+        let mut rng = WyRng::seeded(0xeeee);
+        let (small, inno_head) = T::new(2, 1);
+        let mut innogen = InnoGen::new(inno_head);
+        let mut large = small.clone();
+        large.bisect_connection(&mut rng, &mut innogen).unwrap();
+
+        let mut search = MapElites::new(
+            (vec![], innogen.head),
+            grid(),
+            100,
+            EvolutionConfig {
+                crossover_rate: 1.,
+                mutation: MutationConfig {
+                    new_connection: 0,
+                    bisect_connection: 0,
+                    mutate_connection: 0,
+                    ..MutationConfig::default()
+                },
+                ..EvolutionConfig::default()
+            },
+            rng,
+        );
+        search.elites.insert(small, 1., vec![0., 4.]);
+        search.elites.insert(large.clone(), 2., vec![9., 9.]);
+
+        // crossed with the other, every child takes the structure of the fitter and larger elite
+        search.step().unwrap();
+        assert!(search
+            .ask()
+            .iter()
+            .all(|child| child.nodes().len() == large.nodes().len()));
+    });
+
+    test_t!(test_map_elites_resume[T: G]() {
+        let mut search = MapElites::new(
+            population_init::<C, T>(2, 1, 10),
+            grid(),
+            10,
+            EvolutionConfig::default(),
+            WyRng::seeded(0xeeee),
+        );
+        assert_eq!(
+            search.tell(vec![(0., vec![0., 0.]); 9]),
+            Err(EvolutionError::Mismatched { wanted: 10, got: 9 })
+        );
+        for _ in 0..5 {
+            search.eval(&WeightShape);
+            search.step().unwrap();
+        }
+        assert_eq!(
+            search.step(),
+            Err(EvolutionError::Unevaluated {
+                pending: 10,
+                generation: 5
+            })
+        );
+
+        let mut resumed = MapElites::<C, T, WyRng>::from_str(&search.to_string().unwrap()).unwrap();
+        for _ in 0..5 {
+            search.eval(&WeightShape);
+            resumed.eval(&WeightShape);
+            search.step().unwrap();
+            resumed.step().unwrap();
+        }
+        assert_eq!(search.to_string().unwrap(), resumed.to_string().unwrap());
+    });
+
+    test_t!(test_elites_to_files[T: G]() {
+        let mut search = MapElites::new(
+            population_init::<C, T>(2, 1, 10),
+            grid(),
+            20,
+            EvolutionConfig::default(),
+            WyRng::seeded(0xeeee),
+        );
+        for _ in 0..10 {
+            search.eval(&WeightShape);
+            search.step().unwrap();
+        }
+        search.eval(&WeightShape);
+
+        let path = std::env::temp_dir().join(format!("eevee-elites-{}", std::process::id()));
+        fs::create_dir_all(&path).unwrap();
+        search.elites().to_files(&path).unwrap();
+
+        let table = fs::read_to_string(path.join("elites.csv")).unwrap();
+        let mut lines = table.lines();
+        assert_eq!(lines.next(), Some("cell,fitness,behavior_0,behavior_1"));
+        for (line, (cell, elite)) in lines.zip(search.elites().iter()) {
+            let fields = line.split(',').collect::<Vec<_>>();
+            assert_eq!(fields[0].parse::<usize>().unwrap(), cell);
+            assert_eq!(fields[1].parse::<f64>().unwrap(), elite.fitness);
+            let genome = T::from_file(path.join(format!("{cell}.json"))).unwrap();
+            assert_eq!(genome.to_string().unwrap(), elite.genome.to_string().unwrap());
+        }
+        assert_eq!(table.lines().count(), search.elites().len() + 1);
+        fs::remove_dir_all(&path).unwrap();
+    });
+}
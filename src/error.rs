@@ -0,0 +1,51 @@
+//! Errors that may stop a generation from evolving into the next one.
+
+use core::{error::Error, fmt};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum EvolutionError {
+    /// A genome has nowhere left to form a new connection, and no connection to bisect instead
+    Saturated,
+    /// A genome has no connections to bisect
+    Disconnected,
+    /// There are too few members to produce children from, such as crossover with only one
+    TooFewMembers { produce: usize, members: usize },
+    /// A fitness can't be ordered against any other, which is only true of NaN
+    Incomparable(f64),
+    /// Some genomes of a generation were never told their fitness
+    Unevaluated { pending: usize, generation: usize },
+    /// A generation was told its fitness with no genomes pending, such as being told twice
+    AlreadyEvaluated { generation: usize },
+    /// The wrong number of fitnesses ( or objectives, behaviors, scores ) were given
+    Mismatched { wanted: usize, got: usize },
+    /// A [CoevolutionScenario](crate::CoevolutionScenario) has nobody play in its matches
+    NoPlayers,
+}
+
+impl fmt::Display for EvolutionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Saturated => write!(f, "connections on genome are fully saturated"),
+            Self::Disconnected => write!(f, "no connections available to bisect"),
+            Self::TooFewMembers { produce, members } => write!(
+                f,
+                "too few members to reproduce (wanted to produce {produce} from {members})"
+            ),
+            Self::Incomparable(fitness) => write!(f, "cannot compare fitness {fitness}"),
+            Self::Unevaluated {
+                pending,
+                generation,
+            } => write!(
+                f,
+                "{pending} genomes of generation {generation} have not been evaluated"
+            ),
+            Self::AlreadyEvaluated { generation } => {
+                write!(f, "generation {generation} has already been evaluated")
+            }
+            Self::Mismatched { wanted, got } => write!(f, "wanted {wanted}, but got {got}"),
+            Self::NoPlayers => write!(f, "matches must have at least 1 player"),
+        }
+    }
+}
+
+impl Error for EvolutionError {}
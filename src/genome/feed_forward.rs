@@ -0,0 +1,351 @@
+use super::{Connection, Genome, InnoGen, Neuron, Node};
+use crate::{
+    crossover::{crossover, crossover_nodes},
+    serialize::{deserialize_connections, deserialize_nodes},
+};
+use core::cmp::{max, Ordering};
+use fxhash::FxHashMap;
+use rand::{seq::IteratorRandom, RngCore};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+
+/// A genome whose connections always form a directed acyclic graph, so that any network it
+/// describes may be evaluated in a single pass. Connections who would close a cycle are never
+/// opened by mutation, and are dropped from crossover children.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FeedForward<C: Connection, N: Node = Neuron> {
+    sensory: usize,
+    action: usize,
+    #[serde(deserialize_with = "deserialize_nodes")]
+    nodes: Vec<N>,
+    #[serde(deserialize_with = "deserialize_connections")]
+    connections: Vec<C>,
+}
+
+impl<C: Connection, N: Node> FeedForward<C, N> {
+    /// Nodes who may reach `node` through some path of connections, including `node` itself.
+    /// Disabled connections are considered, as they may be enabled again through crossover.
+    fn ancestors(&self, node: usize) -> HashSet<usize> {
+        let mut seen = HashSet::from([node]);
+        let mut frontier = vec![node];
+        while let Some(n) = frontier.pop() {
+            for c in self.connections.iter().filter(|c| c.to() == n) {
+                if seen.insert(c.from()) {
+                    frontier.push(c.from());
+                }
+            }
+        }
+
+        seen
+    }
+}
+
+/// Whether `target` may be reached by walking `children` from `start`
+fn reaches(children: &FxHashMap<usize, Vec<usize>>, start: usize, target: usize) -> bool {
+    let mut seen = HashSet::from([start]);
+    let mut frontier = vec![start];
+    while let Some(n) = frontier.pop() {
+        if n == target {
+            return true;
+        }
+
+        for child in children.get(&n).into_iter().flatten() {
+            if seen.insert(*child) {
+                frontier.push(*child);
+            }
+        }
+    }
+
+    false
+}
+
+/// Keep connections, oldest to newest, so long as they don't close a cycle with those kept
+/// before them
+fn acyclic<C: Connection>(connections: Vec<C>) -> Vec<C> {
+    let mut children = FxHashMap::<usize, Vec<usize>>::default();
+    connections
+        .into_iter()
+        .filter(|c| {
+            if reaches(&children, c.to(), c.from()) {
+                false
+            } else {
+                children.entry(c.from()).or_default().push(c.to());
+                true
+            }
+        })
+        .collect()
+}
+
+impl<C: Connection, N: Node> Genome<C> for FeedForward<C, N> {
+    type Node = N;
+
+    fn new(sensory: usize, action: usize) -> (Self, usize) {
+        let mut nodes = vec![N::default(); sensory + action];
+        nodes.push(N::new_static());
+
+        let mut inno = InnoGen::new(0);
+        let mut connections = Vec::new();
+        for from in (0..sensory).chain([sensory + action]) {
+            for to in sensory..sensory + action {
+                connections.push(C::new(from, to, &mut inno));
+            }
+        }
+
+        (
+            Self {
+                sensory,
+                action,
+                nodes,
+                connections,
+            },
+            inno.head,
+        )
+    }
+
+    fn sensory(&self) -> std::ops::Range<usize> {
+        0..self.sensory
+    }
+
+    fn action(&self) -> std::ops::Range<usize> {
+        self.sensory..self.sensory + self.action
+    }
+
+    fn nodes(&self) -> &[N] {
+        &self.nodes
+    }
+
+    fn nodes_mut(&mut self) -> &mut [N] {
+        &mut self.nodes
+    }
+
+    fn push_node(&mut self, node: N) {
+        self.nodes.push(node);
+    }
+
+    fn connections(&self) -> &[C] {
+        &self.connections
+    }
+
+    fn connections_mut(&mut self) -> &mut [C] {
+        &mut self.connections
+    }
+
+    fn push_connection(&mut self, connection: C) {
+        self.connections.push(connection);
+    }
+
+    /// Like [Recurrent](super::Recurrent::open_path), but never picks a path whose destination
+    /// may already reach its source
+    fn open_path(&self, rng: &mut impl RngCore) -> Option<(usize, usize)> {
+        let mut saturated = HashSet::new();
+        loop {
+            let from = (0..self.nodes.len())
+                .filter(|from| !self.action().contains(from) && !saturated.contains(from))
+                .choose(rng)?;
+
+            let mut exclude = self.ancestors(from);
+            exclude.extend(
+                self.connections
+                    .iter()
+                    .filter_map(|c| (c.from() == from).then_some(c.to())),
+            );
+
+            if let Some(to) = (0..self.nodes.len())
+                .filter(|to| {
+                    !self.sensory().contains(to)
+                        && *to != self.static_node()
+                        && !exclude.contains(to)
+                })
+                .choose(rng)
+            {
+                break Some((from, to));
+            }
+
+            saturated.insert(from);
+        }
+    }
+
+    fn reproduce_with(&self, other: &Self, self_fit: Ordering, rng: &mut impl RngCore) -> Self {
+        let connections = acyclic(crossover(
+            &self.connections,
+            &other.connections,
+            self_fit,
+            rng,
+        ));
+        let nodes_size = connections
+            .iter()
+            .fold(0, |prev, c| max(prev, max(c.from(), c.to())));
+
+        let nodes = crossover_nodes(
+            self,
+            other,
+            self_fit,
+            max(self.sensory + self.action, nodes_size) + 1,
+            rng,
+        );
+
+        debug_assert!(
+            connections
+                .iter()
+                .fold(0, |acc, c| max(acc, max(c.from(), c.to())))
+                < nodes.len()
+        );
+
+        Self {
+            sensory: self.sensory,
+            action: self.action,
+            nodes,
+            connections,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        config::MutationConfig,
+        error::EvolutionError,
+        genome::{InnoGen, WConnection},
+        random::default_rng,
+        test_t,
+    };
+    use rand::Rng;
+
+    type C = WConnection;
+    type FeedForwardC = FeedForward<C>;
+
+    fn assert_acyclic<C: Connection>(connections: &[C]) {
+        let children = connections.iter().fold(
+            FxHashMap::<usize, Vec<usize>>::default(),
+            |mut children, c| {
+                children.entry(c.from()).or_default().push(c.to());
+                children
+            },
+        );
+
+        for c in connections {
+            assert!(
+                !reaches(&children, c.to(), c.from()),
+                "{:?} closes a cycle in {connections:?}",
+                c.path()
+            );
+        }
+    }
+
+    test_t!(
+    test_genome_creation[T: FeedForwardC]() {
+        let (genome, inno_head) = T::new(3, 2);
+        assert_eq!(inno_head, 8);
+        assert_eq!(genome.nodes().len(), 6);
+        assert!(genome
+            .connections()
+            .iter()
+            .all(|c| genome.action().contains(&c.to())));
+        assert_acyclic(genome.connections());
+    });
+
+    test_t!(
+    test_open_path_acyclic[T: FeedForwardC]() {
+        let mut inno = InnoGen::new(0);
+        let (mut genome, _) = T::new(0, 1);
+        genome.connections = vec![];
+        genome.push_node(Neuron::default());
+        genome.push_node(Neuron::default());
+        genome.push_connection(C::new(2, 3, &mut inno));
+
+        for _ in 0..100 {
+            match genome.open_path(&mut default_rng()) {
+                Some((3, 2)) | Some((2, 2)) | Some((3, 3)) => unreachable!("cycle gen'd"),
+                Some(_) => {}
+                None => unreachable!("no path gen'd"),
+            }
+        }
+    });
+
+    test_t!(
+    test_open_path_saturated[T: FeedForwardC]() {
+        let mut inno = InnoGen::new(0);
+        let (mut genome, _) = T::new(1, 1);
+        genome
+            .bisect_connection(&mut default_rng(), &mut inno)
+            .unwrap();
+
+        // only static -> bisected node is open, its reverse would form a cycle
+        let open = genome.open_path(&mut default_rng()).unwrap();
+        for _ in 0..100 {
+            assert_eq!(genome.open_path(&mut default_rng()), Some(open));
+        }
+
+        genome.push_connection(C::new(open.0, open.1, &mut inno));
+        assert_eq!(genome.open_path(&mut default_rng()), None);
+        assert_eq!(
+            genome.new_connection(&mut default_rng(), &mut inno),
+            Err(EvolutionError::Saturated)
+        );
+
+        // a saturated genome bisects rather than failing to connect
+        let config = MutationConfig {
+            new_connection: 100,
+            bisect_connection: 0,
+            mutate_connection: 0,
+            ..MutationConfig::default()
+        };
+        let nodes = genome.nodes().len();
+        genome.mutate(&mut default_rng(), &mut inno, &config).unwrap();
+        assert_eq!(genome.nodes().len(), nodes + 1);
+        assert_acyclic(genome.connections());
+    });
+
+    test_t!(
+    test_reproduce_drops_cycles[T: FeedForwardC]() {
+        let mut inno = InnoGen::new(0);
+        let (mut l, _) = T::new(0, 1);
+        l.connections = vec![];
+        l.push_node(Neuron::default());
+        l.push_node(Neuron::default());
+        let mut r = l.clone();
+
+        l.push_connection(C::new(2, 3, &mut inno));
+        r.push_connection(C::new(3, 2, &mut inno));
+        assert_acyclic(l.connections());
+        assert_acyclic(r.connections());
+
+        let mut rng = default_rng();
+        for _ in 0..100 {
+            let child = l.reproduce_with(&r, Ordering::Equal, &mut rng);
+            assert_eq!(child.connections().len(), 1);
+            assert_eq!(child.connections()[0].path(), (2, 3));
+        }
+    });
+
+    test_t!(
+    test_mutate_reproduce_acyclic[T: FeedForwardC]() {
+        let config = MutationConfig {
+            new_connection: 45,
+            bisect_connection: 45,
+            mutate_connection: 10,
+            ..MutationConfig::default()
+        };
+
+        let mut rng = default_rng();
+        let (genome, inno_head) = T::new(3, 2);
+        let mut inno = InnoGen::new(inno_head);
+        let mut pool = vec![genome; 10];
+        for _ in 0..200 {
+            let l = &pool[rng.random_range(0..pool.len())];
+            let r = &pool[rng.random_range(0..pool.len())];
+            let fit = [Ordering::Less, Ordering::Equal, Ordering::Greater][rng.random_range(0..3)];
+
+            let mut child = l.reproduce_with(r, fit, &mut rng);
+            assert_acyclic(child.connections());
+            for _ in 0..rng.random_range(1..4) {
+                child.mutate(&mut rng, &mut inno, &config).unwrap();
+                assert_acyclic(child.connections());
+            }
+
+            let idx = rng.random_range(0..pool.len());
+            pool[idx] = child;
+        }
+    });
+}
@@ -9,12 +9,20 @@
 //! complex behavior. Through evolution, that complex behavior is refined towards increasing
 //! some one-dimensional fitness.
 pub mod connection;
+pub mod feed_forward;
+pub mod node;
 pub mod recurrent;
 
 pub use connection::WConnection;
+pub use feed_forward::FeedForward;
+pub use node::{Neuron, Node};
 pub use recurrent::Recurrent;
 
-use crate::random::{percent, ConnectionEvent, EventKind, GenomeEvent};
+use crate::{
+    config::MutationConfig,
+    error::EvolutionError,
+    random::{percent, ConnectionEvent, EventKind, GenomeEvent},
+};
 use core::{cmp::Ordering, error::Error, fmt::Debug, hash::Hash, ops::Range};
 use fxhash::FxHashMap;
 use rand::{Rng, RngCore};
@@ -52,28 +60,15 @@ impl InnoGen {
     }
 }
 
-/// This has no reason to exist, and will be replaced with ranges in the future.
-#[deprecated]
-#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
-pub enum NodeKind {
-    Sensory,
-    Action,
-    Internal,
-    Static,
-}
-
 /// A connection between 2 points. Connections may be arbitrarially parameterized, and those
-/// parameters mutated inside [mutate_param](Connection::mutate_param). For those params to
+/// parameters mutated inside [mutate_param](Connection::mutate_param), whose probabilities are
+/// described by a [MutationConfig]. For those params to
 /// actually be _used_, a connection should expose them with a trait, and a
 /// [Network](crate::network::Network) implementer should know about them. Any connection must
 /// have a path, weight, and innovation_id ( which should be supplied from InnoGen ).
 pub trait Connection:
     Serialize + for<'de> Deserialize<'de> + Clone + Hash + PartialEq + Default + Debug
 {
-    const PROBABILITIES: [u64; ConnectionEvent::COUNT] = [percent(1), percent(99)];
-    const PARAM_REPLACE_PROBABILITY: u64 = percent(10);
-    const PARAM_PERTURB_FAC: f64 = 0.05;
-
     const EXCESS_COEFFICIENT: f64;
     const DISJOINT_COEFFICIENT: f64;
     const PARAM_COEFFICIENT: f64;
@@ -115,14 +110,14 @@ pub trait Connection:
     fn param_diff(&self, other: &Self) -> f64;
 
     /// possibly mutate a single param
-    fn mutate_param(&mut self, rng: &mut impl RngCore);
+    fn mutate_param(&mut self, rng: &mut impl RngCore, config: &MutationConfig);
 
     /// mutate a connection
-    fn mutate(&mut self, rng: &mut impl RngCore) {
-        if let Some(evt) = ConnectionEvent::pick(rng, Self::PROBABILITIES) {
+    fn mutate(&mut self, rng: &mut impl RngCore, config: &MutationConfig) {
+        if let Some(evt) = ConnectionEvent::pick(rng, config.connection_probabilities()) {
             match evt {
                 ConnectionEvent::Disable => self.disable(),
-                ConnectionEvent::MutateParam => self.mutate_param(rng),
+                ConnectionEvent::MutateParam => self.mutate_param(rng, config),
             }
         }
     }
@@ -136,10 +131,7 @@ pub trait Connection:
 /// arbitrary parameters. A genome must also be able to reproduce with any other genome of the
 /// same kind, their connections constructively crossing over.
 pub trait Genome<C: Connection>: Serialize + for<'de> Deserialize<'de> + Clone {
-    const MUTATE_NODE_PROBABILITY: u64 = percent(20);
-    const MUTATE_CONNECTION_PROBABILITY: u64 = percent(20);
-    const PROBABILITIES: [u64; GenomeEvent::COUNT] =
-        [percent(5), percent(15), percent(80), percent(0)];
+    type Node: Node;
 
     /// A new genome of this type, with a known input and output size.
     fn new(sensory: usize, action: usize) -> (Self, usize);
@@ -148,13 +140,23 @@ pub trait Genome<C: Connection>: Serialize + for<'de> Deserialize<'de> + Clone {
 
     fn action(&self) -> Range<usize>;
 
-    fn nodes(&self) -> &[NodeKind];
+    /// The static node, whose output is always on. Nodes are laid out as sensory, then action,
+    /// then static, then internal.
+    fn static_node(&self) -> usize {
+        self.action().end
+    }
+
+    /// Nodes grown through bisection, who are neither sensory, action, nor static
+    fn internal(&self) -> Range<usize> {
+        self.static_node() + 1..self.nodes().len()
+    }
+
+    fn nodes(&self) -> &[Self::Node];
 
-    #[deprecated]
-    fn nodes_mut(&mut self) -> &mut [NodeKind];
+    fn nodes_mut(&mut self) -> &mut [Self::Node];
 
     /// Push a new node onto the genome.
-    fn push_node(&mut self, node: NodeKind);
+    fn push_node(&mut self, node: Self::Node);
 
     /// A collection to the connections comprising this genome.
     fn connections(&self) -> &[C];
@@ -173,13 +175,36 @@ pub trait Genome<C: Connection>: Serialize + for<'de> Deserialize<'de> + Clone {
         self.push_connection(second);
     }
 
-    /// Possibly mutate a single connection. On average, will mutate every
-    /// [MUTATE_CONNECTION_PROBABILITY](Genome::MUTATE_CONNECTION_PROBABILITY) / [u64::MAX]
-    /// connection.
-    fn mutate_connection(&mut self, rng: &mut impl RngCore) {
+    /// Possibly mutate a single connection. On average, will mutate
+    /// [connection_rate](MutationConfig::connection_rate) percent of connections.
+    fn mutate_connection(&mut self, rng: &mut impl RngCore, config: &MutationConfig) {
+        let rate = percent(config.connection_rate);
         for c in self.connections_mut() {
-            if rng.next_u64() < Self::MUTATE_CONNECTION_PROBABILITY {
-                c.mutate(rng);
+            if rng.next_u64() < rate {
+                c.mutate(rng, config);
+            }
+        }
+    }
+
+    /// Possibly mutate a single param of internal and action nodes. On average, will mutate
+    /// [node_rate](MutationConfig::node_rate) percent of them.
+    fn mutate_node(&mut self, rng: &mut impl RngCore, config: &MutationConfig) {
+        let rate = percent(config.node_rate);
+        for node in self.action().chain(self.internal()) {
+            if rng.next_u64() < rate {
+                self.nodes_mut()[node].mutate_param(rng, config);
+            }
+        }
+    }
+
+    /// Possibly change the activation of internal and action nodes. On average, will change
+    /// [node_rate](MutationConfig::node_rate) percent of them.
+    fn mutate_activation(&mut self, rng: &mut impl RngCore, config: &MutationConfig) {
+        let rate = percent(config.node_rate);
+        for node in self.action().chain(self.internal()) {
+            if rng.next_u64() < rate {
+                let activation = self.nodes()[node].activation().mutate(rng);
+                self.nodes_mut()[node].set_activation(activation);
             }
         }
     }
@@ -195,12 +220,12 @@ pub trait Genome<C: Connection>: Serialize + for<'de> Deserialize<'de> + Clone {
         &mut self,
         rng: &mut impl RngCore,
         inno: &mut InnoGen,
-    ) -> Result<(), Box<dyn Error>> {
+    ) -> Result<(), EvolutionError> {
         if let Some((from, to)) = self.open_path(rng) {
             self.push_connection(C::new(from, to, inno));
             Ok(())
         } else {
-            Err("connections on genome are fully saturated".into())
+            Err(EvolutionError::Saturated)
         }
     }
 
@@ -211,9 +236,9 @@ pub trait Genome<C: Connection>: Serialize + for<'de> Deserialize<'de> + Clone {
         &mut self,
         rng: &mut impl RngCore,
         inno: &mut InnoGen,
-    ) -> Result<(), Box<dyn Error>> {
+    ) -> Result<(), EvolutionError> {
         if self.connections().is_empty() {
-            return Err("no connections available to bisect".into());
+            return Err(EvolutionError::Disconnected);
         }
 
         let center = self.nodes().len();
@@ -224,30 +249,40 @@ pub trait Genome<C: Connection>: Serialize + for<'de> Deserialize<'de> + Clone {
             .unwrap()
             .bisect(center, inno);
 
-        self.push_node(NodeKind::Internal);
+        self.push_node(Self::Node::default());
         self.push_2_connections(lower, upper);
         Ok(())
     }
 
-    /// Perform 0 or more mutations on this genome. If [PROBABILITIES](Genome::PROBABILITIES)
-    /// add up to [u64::MAX], some event will always be picked. Otherwise, it's possible that
-    /// no mutation actually ocurrs.
+    /// Perform 0 or more mutations on this genome. If the genome event probabilities of
+    /// `config` add up to 100, some event will always be picked. Otherwise, it's possible that
+    /// no mutation actually ocurrs. A genome too saturated for a new connection bisects one
+    /// instead, and so this only fails if a genome without connections has nowhere to form one.
+    /// Any nodes grown take the [activation](MutationConfig::activation) of `config`.
     fn mutate(
         &mut self,
         rng: &mut impl RngCore,
         innogen: &mut InnoGen,
-    ) -> Result<(), Box<dyn Error>> {
+        config: &MutationConfig,
+    ) -> Result<(), EvolutionError> {
+        let size = self.nodes().len();
         if self.connections().is_empty() {
             self.new_connection(rng, innogen)?;
-        } else if let Some(evt) = GenomeEvent::pick(rng, Self::PROBABILITIES) {
+        } else if let Some(evt) = GenomeEvent::pick(rng, config.genome_probabilities()) {
             match evt {
-                GenomeEvent::NewConnection => self.new_connection(rng, innogen)?,
+                GenomeEvent::NewConnection => self
+                    .new_connection(rng, innogen)
+                    .or_else(|_| self.bisect_connection(rng, innogen))?,
                 GenomeEvent::BisectConnection => self.bisect_connection(rng, innogen)?,
-                GenomeEvent::MutateConnection => self.mutate_connection(rng),
-                GenomeEvent::MutateNode => unreachable!("nodes may not be mutated"),
+                GenomeEvent::MutateConnection => self.mutate_connection(rng, config),
+                GenomeEvent::MutateNode => self.mutate_node(rng, config),
+                GenomeEvent::MutateActivation => self.mutate_activation(rng, config),
             };
         }
 
+        for node in self.nodes_mut()[size..].iter_mut() {
+            node.set_activation(config.activation);
+        }
         Ok(())
     }
 
@@ -0,0 +1,175 @@
+use crate::{activate::Activation, config::MutationConfig, random::percent};
+use core::fmt::Debug;
+use rand::{distr::Uniform, Rng, RngCore};
+use serde::{Deserialize, Serialize};
+
+/// A node in a genome. Where a [Connection](super::Connection) describes a path between nodes,
+/// a node describes what happens at either end of it. Like connections, nodes may be
+/// arbitrarially parameterized, and those params mutated inside
+/// [mutate_param](Node::mutate_param). For those params to actually be _used_, a
+/// [Network](crate::network::Network) implementer should know about them.
+///
+/// Nodes aren't identified by innovation ids, but by their index in a genome, which lays them
+/// out as sensory, then action, then a single static node, then internal nodes.
+pub trait Node:
+    Serialize + for<'de> Deserialize<'de> + Clone + PartialEq + Default + Debug
+{
+    /// The static node of a genome, whose output is always on
+    fn new_static() -> Self;
+
+    /// constant added to the input of this node
+    fn bias(&self) -> f64;
+
+    /// membrane time constant of this node, for networks who integrate over time
+    fn tau(&self) -> f64;
+
+    fn activation(&self) -> Activation;
+
+    fn set_activation(&mut self, activation: Activation);
+
+    /// difference of node parameters ( for example, bias ) between this and another node at
+    /// the same index, which is never negative. Unlike connections, it isn't part of a
+    /// [delta](crate::crossover::delta), so doesn't weigh into speciation.
+    fn param_diff(&self, other: &Self) -> f64;
+
+    /// possibly mutate a single param
+    fn mutate_param(&mut self, rng: &mut impl RngCore, config: &MutationConfig);
+}
+
+/// A basic node, with a bias, time constant, and activation. Its time constant is always kept
+/// within [TAU_MIN](Neuron::TAU_MIN)..=[TAU_MAX](Neuron::TAU_MAX), so that it stays positive
+/// and small enough for networks to integrate stably.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Neuron {
+    pub bias: f64,
+    pub tau: f64,
+    pub activation: Activation,
+}
+
+impl Default for Neuron {
+    fn default() -> Self {
+        Self {
+            bias: 0.,
+            tau: 0.1,
+            activation: Activation::default(),
+        }
+    }
+}
+
+impl Neuron {
+    pub const TAU_MIN: f64 = 0.01;
+    pub const TAU_MAX: f64 = 1.;
+}
+
+impl Node for Neuron {
+    fn mutate_param(&mut self, rng: &mut impl RngCore, config: &MutationConfig) {
+        let replace = rng.next_u64() < percent(config.param_replace);
+        let v: f64 =
+            rng.sample(Uniform::new_inclusive(-3., 3.).expect("distribution of -3. ..= 3. failed"));
+        if rng.next_u64() < percent(50) {
+            self.bias = if replace {
+                v
+            } else {
+                self.bias + config.param_perturb * v
+            };
+        } else {
+            self.tau = if replace {
+                rng.sample(
+                    Uniform::new_inclusive(Self::TAU_MIN, Self::TAU_MAX)
+                        .expect("distribution of tau failed"),
+                )
+            } else {
+                (self.tau + config.param_perturb * v).clamp(Self::TAU_MIN, Self::TAU_MAX)
+            };
+        }
+    }
+
+    fn param_diff(&self, other: &Self) -> f64 {
+        (self.bias - other.bias).abs() + (self.tau - other.tau).abs()
+    }
+
+    fn new_static() -> Self {
+        Self {
+            bias: 1.,
+            ..Self::default()
+        }
+    }
+
+    fn bias(&self) -> f64 {
+        self.bias
+    }
+
+    fn tau(&self) -> f64 {
+        self.tau
+    }
+
+    fn activation(&self) -> Activation {
+        self.activation
+    }
+
+    fn set_activation(&mut self, activation: Activation) {
+        self.activation = activation;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::random::default_rng;
+
+    #[test]
+    fn test_mutate_param() {
+        let mut rng = default_rng();
+        let config = MutationConfig {
+            param_replace: 100,
+            ..MutationConfig::default()
+        };
+
+        let mut node = Neuron::default();
+        while node.bias == 0. || node.tau == 0.1 {
+            node.mutate_param(&mut rng, &config);
+        }
+        assert_eq!(node.activation, Activation::default());
+        assert!((Neuron::TAU_MIN..=Neuron::TAU_MAX).contains(&node.tau));
+        assert_eq!(
+            node.param_diff(&Neuron::default()),
+            node.bias.abs() + (node.tau - 0.1).abs()
+        );
+
+        // differences in opposite directions don't cancel out
+        let other = Neuron {
+            bias: 1.,
+            tau: 1.1,
+            ..Neuron::default()
+        };
+        let node = Neuron {
+            bias: 2.,
+            tau: 0.1,
+            ..Neuron::default()
+        };
+        assert_eq!(node.param_diff(&other), 2.);
+        assert_eq!(other.param_diff(&node), 2.);
+    }
+
+    #[test]
+    fn test_tau_positive() {
+        let mut rng = default_rng();
+        for config in [
+            MutationConfig {
+                param_replace: 100,
+                ..MutationConfig::default()
+            },
+            MutationConfig {
+                param_replace: 0,
+                param_perturb: 10.,
+                ..MutationConfig::default()
+            },
+        ] {
+            let mut node = Neuron::default();
+            for _ in 0..1000 {
+                node.mutate_param(&mut rng, &config);
+                assert!((Neuron::TAU_MIN..=Neuron::TAU_MAX).contains(&node.tau));
+            }
+        }
+    }
+}
@@ -1,6 +1,6 @@
-use super::{Connection, Genome, InnoGen, NodeKind};
+use super::{Connection, Genome, InnoGen, Neuron, Node};
 use crate::{
-    crossover::crossover,
+    crossover::{crossover, crossover_nodes},
     serialize::{deserialize_connections, deserialize_nodes},
 };
 use core::cmp::{max, Ordering};
@@ -10,38 +10,26 @@ use std::collections::HashSet;
 
 /// A genome that allows recurrent connections
 #[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct Recurrent<C: Connection> {
+pub struct Recurrent<C: Connection, N: Node = Neuron> {
     sensory: usize,
     action: usize,
     #[serde(deserialize_with = "deserialize_nodes")]
-    nodes: Vec<NodeKind>,
+    nodes: Vec<N>,
     #[serde(deserialize_with = "deserialize_connections")]
     connections: Vec<C>,
 }
 
-impl<C: Connection> Genome<C> for Recurrent<C> {
+impl<C: Connection, N: Node> Genome<C> for Recurrent<C, N> {
+    type Node = N;
+
     fn new(sensory: usize, action: usize) -> (Self, usize) {
-        let mut nodes = Vec::with_capacity(sensory + action + 1);
-        for _ in 0..sensory {
-            nodes.push(NodeKind::Sensory);
-        }
-        for _ in sensory..sensory + action {
-            nodes.push(NodeKind::Action);
-        }
-        nodes.push(NodeKind::Static);
+        let mut nodes = vec![N::default(); sensory + action];
+        nodes.push(N::new_static());
 
         let mut inno = InnoGen::new(0);
         let mut connections = Vec::new();
-        for (from, _) in nodes
-            .iter()
-            .enumerate()
-            .filter(|(_, n)| matches!(n, NodeKind::Sensory | NodeKind::Static | NodeKind::Internal))
-        {
-            for (to, _) in nodes
-                .iter()
-                .enumerate()
-                .filter(|(to, n)| *to != from && matches!(n, NodeKind::Internal | NodeKind::Action))
-            {
+        for from in (0..sensory).chain([sensory + action]) {
+            for to in sensory..sensory + action {
                 connections.push(C::new(from, to, &mut inno));
             }
         }
@@ -65,15 +53,15 @@ impl<C: Connection> Genome<C> for Recurrent<C> {
         self.sensory..self.sensory + self.action
     }
 
-    fn nodes(&self) -> &[NodeKind] {
+    fn nodes(&self) -> &[N] {
         &self.nodes
     }
 
-    fn nodes_mut(&mut self) -> &mut [NodeKind] {
+    fn nodes_mut(&mut self) -> &mut [N] {
         &mut self.nodes
     }
 
-    fn push_node(&mut self, node: NodeKind) {
+    fn push_node(&mut self, node: N) {
         self.nodes.push(node);
     }
 
@@ -92,13 +80,8 @@ impl<C: Connection> Genome<C> for Recurrent<C> {
     fn open_path(&self, rng: &mut impl RngCore) -> Option<(usize, usize)> {
         let mut saturated = HashSet::new();
         loop {
-            let (from, _) = self
-                .nodes()
-                .iter()
-                .enumerate()
-                .filter(|(from, node)| {
-                    !matches!(node, NodeKind::Action) && !saturated.contains(from)
-                })
+            let from = (0..self.nodes.len())
+                .filter(|from| !self.action().contains(from) && !saturated.contains(from))
                 .choose(rng)?;
 
             let exclude = self
@@ -107,12 +90,11 @@ impl<C: Connection> Genome<C> for Recurrent<C> {
                 .filter_map(|c| (c.from() == from).then_some(c.to()))
                 .collect::<HashSet<_>>();
 
-            if let Some((to, _)) = self
-                .nodes()
-                .iter()
-                .enumerate()
-                .filter(|(to, node)| {
-                    !matches!(node, NodeKind::Static | NodeKind::Sensory) && !exclude.contains(to)
+            if let Some(to) = (0..self.nodes.len())
+                .filter(|to| {
+                    !self.sensory().contains(to)
+                        && *to != self.static_node()
+                        && !exclude.contains(to)
                 })
                 .choose(rng)
             {
@@ -129,17 +111,13 @@ impl<C: Connection> Genome<C> for Recurrent<C> {
             .iter()
             .fold(0, |prev, c| max(prev, max(c.from(), c.to())));
 
-        let mut nodes = Vec::with_capacity(self.sensory + self.action + 1);
-        for _ in 0..self.sensory {
-            nodes.push(NodeKind::Sensory);
-        }
-        for _ in self.sensory..self.sensory + self.action {
-            nodes.push(NodeKind::Action);
-        }
-        nodes.push(NodeKind::Static);
-        for _ in self.sensory + self.action..nodes_size {
-            nodes.push(NodeKind::Internal);
-        }
+        let nodes = crossover_nodes(
+            self,
+            other,
+            self_fit,
+            max(self.sensory + self.action, nodes_size) + 1,
+            rng,
+        );
 
         debug_assert!(
             connections
@@ -160,7 +138,10 @@ impl<C: Connection> Genome<C> for Recurrent<C> {
 #[cfg(test)]
 mod test {
     use super::*;
-    use crate::{genome::InnoGen, genome::WConnection, random::default_rng, test_t};
+    use crate::{
+        activate::Activation, config::MutationConfig, error::EvolutionError, genome::InnoGen,
+        genome::WConnection, random::default_rng, test_t,
+    };
 
     type C = WConnection;
     type RecurrentContinuous = Recurrent<C>;
@@ -172,9 +153,9 @@ mod test {
         assert_eq!(genome.sensory().len(), 3);
         assert_eq!(genome.action().len(), 2);
         assert_eq!(genome.nodes().len(), 6);
-        assert!(matches!(genome.nodes[0], NodeKind::Sensory));
-        assert!(matches!(genome.nodes[3], NodeKind::Action));
-        assert!(matches!(genome.nodes[5], NodeKind::Static));
+        assert_eq!(genome.static_node(), 5);
+        assert_eq!(genome.nodes[5], Neuron::new_static());
+        assert!(genome.internal().is_empty());
     });
 
     test_t!(
@@ -184,7 +165,7 @@ mod test {
         assert_eq!(genome.sensory().len(), 0);
         assert_eq!(genome.action().len(), 0);
         assert_eq!(genome.nodes().len(), 1);
-        assert!(matches!(genome.nodes()[0], NodeKind::Static));
+        assert_eq!(genome.static_node(), 0);
     });
 
     test_t!(
@@ -194,9 +175,7 @@ mod test {
         assert_eq!(genome.sensory().len(), 3);
         assert_eq!(genome.action().len(), 0);
         assert_eq!(genome.nodes().len(), 4);
-        assert!(matches!(genome.nodes()[0], NodeKind::Sensory));
-        assert!(matches!(genome.nodes()[2], NodeKind::Sensory));
-        assert!(matches!(genome.nodes()[3], NodeKind::Static));
+        assert_eq!(genome.static_node(), 3);
     });
 
     test_t!(
@@ -206,9 +185,7 @@ mod test {
         assert_eq!(genome.sensory().len(), 0);
         assert_eq!(genome.action().len(), 3);
         assert_eq!(genome.nodes().len(), 4);
-        assert!(matches!(genome.nodes()[0], NodeKind::Action));
-        assert!(matches!(genome.nodes()[2], NodeKind::Action));
-        assert!(matches!(genome.nodes()[3], NodeKind::Static));
+        assert_eq!(genome.static_node(), 3);
     });
 
     test_t!(
@@ -267,7 +244,7 @@ mod test {
         genome.connections = vec![]; // TODO generalize empty connection state
         genome.push_connection({
             let mut c = C::new(0, 1, &mut inno);
-            c.mutate_param(&mut default_rng());
+            c.mutate_param(&mut default_rng(), &MutationConfig::default());
             c
         });
 
@@ -307,7 +284,14 @@ mod test {
     test_mutate_bisection_empty_genome[T: RecurrentContinuous]() {
         let (mut genome, _) = T::new(0, 0);
         genome.connections = vec![]; // TODO generalize empty connection state
-        assert!(genome.bisect_connection(&mut default_rng(), &mut InnoGen::new(0)).is_err());
+        assert_eq!(
+            genome.bisect_connection(&mut default_rng(), &mut InnoGen::new(0)),
+            Err(EvolutionError::Disconnected)
+        );
+        assert_eq!(
+            genome.mutate(&mut default_rng(), &mut InnoGen::new(0), &MutationConfig::default()),
+            Err(EvolutionError::Saturated)
+        );
     });
 
     test_t!(
@@ -316,4 +300,90 @@ mod test {
         genome.connections = vec![]; // TODO generalize empty connection state
         assert!(genome.bisect_connection(&mut default_rng(), &mut InnoGen::new(0)).is_err());
     });
+
+    test_t!(
+    test_mutate_node[T: RecurrentContinuous]() {
+        let (mut genome, _) = T::new(2, 2);
+        genome.push_node(Neuron::default());
+        let before = genome.clone();
+        let config = MutationConfig {
+            new_connection: 0,
+            bisect_connection: 0,
+            mutate_connection: 0,
+            mutate_node: 100,
+            node_rate: 100,
+            param_replace: 100,
+            ..MutationConfig::default()
+        };
+
+        genome.mutate(&mut default_rng(), &mut InnoGen::new(0), &config).unwrap();
+        assert_eq!(genome.connections(), before.connections());
+        for node in 0..genome.nodes().len() {
+            let mutated = genome.nodes()[node].param_diff(&before.nodes()[node]) != 0.;
+            assert_eq!(mutated, !genome.sensory().contains(&node) && node != genome.static_node());
+        }
+    });
+
+    test_t!(
+    test_mutate_activation[T: RecurrentContinuous]() {
+        let (mut genome, _) = T::new(2, 2);
+        genome.push_node(Neuron::default());
+        let config = MutationConfig {
+            node_rate: 100,
+            ..MutationConfig::default()
+        };
+
+        genome.mutate_activation(&mut default_rng(), &config);
+        for (node, neuron) in genome.nodes().iter().enumerate() {
+            assert_eq!(
+                neuron.activation == Activation::default(),
+                genome.sensory().contains(&node) || node == genome.static_node(),
+            );
+        }
+    });
+
+    test_t!(
+    test_reproduce_nodes[T: RecurrentContinuous]() {
+        let (mut l, _) = T::new(2, 2);
+        let mut r = l.clone();
+        l.nodes[2].activation = Activation::Tanh;
+        l.nodes[2].tau = 0.5;
+        r.nodes[2].activation = Activation::Relu;
+        r.nodes[3].activation = Activation::Sin;
+
+        let mut rng = default_rng();
+        let child = l.reproduce_with(&r, Ordering::Greater, &mut rng);
+        assert_eq!(child.nodes()[2], l.nodes()[2]);
+        assert_eq!(child.nodes()[3], l.nodes()[3]);
+        let child = l.reproduce_with(&r, Ordering::Less, &mut rng);
+        assert_eq!(child.nodes()[2], r.nodes()[2]);
+        assert_eq!(child.nodes()[3], r.nodes()[3]);
+        for _ in 0..100 {
+            let child = l.reproduce_with(&r, Ordering::Equal, &mut rng);
+            assert!([&l.nodes()[2], &r.nodes()[2]].contains(&&child.nodes()[2]));
+        }
+    });
+
+    test_t!(
+    test_legacy_nodes[T: RecurrentContinuous]() {
+        // nodes saved as kinds, before they carried params
+        let genome = T::from_file("benches/data/ctr-genome-rand-100.json").unwrap();
+        assert_eq!(genome.sensory().len(), 6);
+        assert!(genome.nodes().iter().all(|node| *node == Neuron::default()));
+
+        let genomes = serde_json::from_str::<Vec<(T, f64)>>(
+            &std::fs::read_to_string("benches/data/ctr-genome-xor-100.json").unwrap(),
+        )
+        .unwrap();
+        assert_eq!(genomes.len(), 93);
+        for (genome, _) in genomes {
+            for (node, neuron) in genome.nodes().iter().enumerate() {
+                if node == genome.static_node() {
+                    assert_eq!(*neuron, Neuron::new_static());
+                } else {
+                    assert_eq!(*neuron, Neuron::default());
+                }
+            }
+        }
+    });
 }
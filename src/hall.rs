@@ -0,0 +1,189 @@
+//! A hall of fame of the fittest genomes ever evaluated, who would otherwise be lost as soon as
+//! their generation reproduces ( or their specie goes extinct ).
+
+use crate::genome::{Connection, Genome};
+use core::{cmp::Ordering, error::Error};
+use serde::{Deserialize, Serialize};
+use std::{fs, path::Path};
+
+/// A genome in the hall of fame, along with its fitness and the generation it was evaluated in
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Inductee<G> {
+    pub genome: G,
+    pub fitness: f64,
+    pub generation: usize,
+}
+
+/// The fittest distinct genomes seen so far, fittest first. Genomes are distinct if they differ
+/// in any node or connection, so that a champion copied unchanged into the next generation
+/// is only inducted once.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HallOfFame<G> {
+    inductees: Vec<Inductee<G>>,
+}
+
+impl<G> Default for HallOfFame<G> {
+    fn default() -> Self {
+        Self {
+            inductees: Vec::new(),
+        }
+    }
+}
+
+impl<G> HallOfFame<G> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.inductees.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.inductees.is_empty()
+    }
+
+    /// Inductees, fittest first
+    pub fn iter(&self) -> impl Iterator<Item = &Inductee<G>> {
+        self.inductees.iter()
+    }
+
+    pub fn get(&self, idx: usize) -> Option<&Inductee<G>> {
+        self.inductees.get(idx)
+    }
+
+    pub fn fittest(&self) -> Option<&Inductee<G>> {
+        self.inductees.first()
+    }
+
+    /// Induct the fittest of `genomes` evaluated in `generation`, keeping at most `size` of
+    /// the fittest inductees. Genomes whose fitness isn't finite are never inducted, and
+    /// genomes already in the hall keep the fitness and generation they were inducted with.
+    pub fn induct<'a, C: Connection>(
+        &mut self,
+        genomes: impl IntoIterator<Item = (&'a G, f64)>,
+        generation: usize,
+        size: usize,
+    ) where
+        G: Genome<C> + 'a,
+    {
+        for (genome, fitness) in genomes {
+            if !fitness.is_finite()
+                || (self.inductees.len() >= size
+                    && self
+                        .inductees
+                        .last()
+                        .is_none_or(|least| fitness <= least.fitness))
+                || self.inductees.iter().any(|Inductee { genome: held, .. }| {
+                    held.connections() == genome.connections() && held.nodes() == genome.nodes()
+                })
+            {
+                continue;
+            }
+
+            // after any of equal fitness, so that the earliest inducted stay ahead
+            let idx = self
+                .inductees
+                .partition_point(|held| held.fitness.total_cmp(&fitness) != Ordering::Less);
+            self.inductees.insert(
+                idx,
+                Inductee {
+                    genome: genome.clone(),
+                    fitness,
+                    generation,
+                },
+            );
+            self.inductees.truncate(size);
+        }
+    }
+}
+
+impl<G: Serialize + for<'de> Deserialize<'de>> HallOfFame<G> {
+    /// Serialize this hall of fame to a JSON string
+    pub fn to_string(&self) -> Result<String, Box<dyn Error>> {
+        Ok(serde_json::to_string(self)?)
+    }
+
+    /// Deserialize a hall of fame from a JSON string
+    #[allow(clippy::should_implement_trait)]
+    pub fn from_str(s: &str) -> Result<Self, Box<dyn Error>> {
+        serde_json::from_str(s).map_err(|op| op.into())
+    }
+
+    pub fn to_file<P: AsRef<Path>>(&self, path: P) -> Result<(), Box<dyn Error>> {
+        fs::write(path, self.to_string()?)?;
+        Ok(())
+    }
+
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self, Box<dyn Error>> {
+        Self::from_str(&fs::read_to_string(path)?)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        genome::{Recurrent, WConnection},
+        macros::fixtures::weighted,
+        test_t,
+    };
+
+    type C = WConnection;
+    type G = Recurrent<C>;
+
+    /// Distinct genomes, each differing in the weight of their first connection
+    fn distinct<T: Genome<C>>(count: usize) -> Vec<T> {
+        weighted((0..count).map(|idx| idx as f64))
+            .into_iter()
+            .map(|(genome, _)| genome)
+            .collect()
+    }
+
+    test_t!(test_induct[T: G]() {
+        let genomes = distinct::<T>(5);
+        let mut hall = HallOfFame::new();
+        hall.induct(genomes.iter().zip([1., 5., f64::NAN, 3., 2.]), 0, 3);
+        assert_eq!(
+            hall.iter().map(|i| i.fitness).collect::<Vec<_>>(),
+            [5., 3., 2.]
+        );
+
+        // the same genome isn't inducted twice, and the least fit fall out
+        let again = [&genomes[1], &genomes[3], &genomes[4], &genomes[2]];
+        hall.induct(again.into_iter().zip([10., 5., 4., f64::INFINITY]), 1, 3);
+        assert_eq!(
+            hall.iter()
+                .map(|i| (i.fitness, i.generation))
+                .collect::<Vec<_>>(),
+            [(5., 0), (3., 0), (2., 0)]
+        );
+        assert_eq!(hall.fittest().unwrap().genome.connections(), genomes[1].connections());
+
+        let newcomers = distinct::<T>(7);
+        hall.induct(newcomers[5..].iter().zip([4., 0.]), 2, 3);
+        assert_eq!(
+            hall.iter()
+                .map(|i| (i.fitness, i.generation))
+                .collect::<Vec<_>>(),
+            [(5., 0), (4., 2), (3., 0)]
+        );
+    });
+
+    test_t!(test_to_file[T: G]() {
+        let genomes = distinct::<T>(4);
+        let mut hall = HallOfFame::new();
+        hall.induct(genomes.iter().zip([1., 2., 3., 4.]), 7, 10);
+
+        let path = std::env::temp_dir().join(format!("eevee-hall-{}.json", std::process::id()));
+        hall.to_file(&path).unwrap();
+        let loaded = HallOfFame::<T>::from_file(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+        assert_eq!(loaded.len(), 4);
+        for (l, r) in hall.iter().zip(loaded.iter()) {
+            assert_eq!(l.fitness, r.fitness);
+            assert_eq!(l.generation, r.generation);
+            assert_eq!(l.genome.connections(), r.genome.connections());
+        }
+    });
+}
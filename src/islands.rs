@@ -0,0 +1,434 @@
+//! The island model, where several populations evolve independently of each other, save for
+//! their fittest genomes periodically migrating between them. Isolated populations tend to
+//! explore different parts of the search space, and migrants carry what they find elsewhere.
+//!
+//! Every island is an [Evolver], with its own species, config, and rng. Islands reproduce from
+//! the same [InnoGen] each generation, so that an innovation id means the same thing on every
+//! island, and migrants can crossover with natives meaningfully.
+
+use crate::{
+    config::{MigrationConfig, Topology},
+    error::EvolutionError,
+    genome::{Connection, Genome, InnoGen},
+    population::FittedGroup,
+    scenario::{Evolver, Scenario},
+    Specie,
+};
+use core::{error::Error, ops::ControlFlow};
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use std::{fs, path::Path};
+
+/// A stepwise handle on the evolution of several islands, who are stepped in lockstep like a
+/// single [Evolver]. Like an evolver, islands whose rng is serializable are a checkpoint of
+/// themselves.
+#[derive(Serialize, Deserialize)]
+#[serde(bound(serialize = "R: Serialize", deserialize = "R: Deserialize<'de>"))]
+pub struct Islands<C: Connection, G: Genome<C>, R: RngCore> {
+    islands: Vec<Evolver<C, G, R>>,
+    config: MigrationConfig,
+}
+
+impl<C: Connection, G: Genome<C>, R: RngCore> Islands<C, G, R> {
+    /// Evolve some islands together, who should all be at the same generation. Each island
+    /// will reproduce from the highest innovation head among them.
+    pub fn new(islands: Vec<Evolver<C, G, R>>, config: MigrationConfig) -> Self {
+        debug_assert!(
+            islands
+                .windows(2)
+                .all(|w| w[0].generation() == w[1].generation()),
+            "islands at different generations"
+        );
+        Self { islands, config }
+    }
+
+    pub fn islands(&self) -> &[Evolver<C, G, R>] {
+        &self.islands
+    }
+
+    /// Mutable access to the islands, so that they may be asked and told individually
+    pub fn islands_mut(&mut self) -> &mut [Evolver<C, G, R>] {
+        &mut self.islands
+    }
+
+    pub fn config(&self) -> &MigrationConfig {
+        &self.config
+    }
+
+    pub fn generation(&self) -> usize {
+        self.islands.first().map_or(0, Evolver::generation)
+    }
+
+    pub fn inno_head(&self) -> usize {
+        self.islands
+            .iter()
+            .map(Evolver::inno_head)
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// The fittest genome of any island, and the island it lives on
+    pub fn fittest(&self) -> Option<(usize, &(G, f64))> {
+        self.islands
+            .iter()
+            .enumerate()
+            .flat_map(|(idx, island)| {
+                island
+                    .species()
+                    .iter()
+                    .filter_map(move |specie| specie.fittest().map(|fittest| (idx, fittest)))
+            })
+            .max_by(|(_, (_, l)), (_, (_, r))| l.total_cmp(r))
+    }
+
+    /// Islands who receive migrants from island `idx`
+    fn neighbors(&self, idx: usize) -> Vec<usize> {
+        let count = self.islands.len();
+        match self.config.topology {
+            Topology::Ring if count > 1 => vec![(idx + 1) % count],
+            Topology::Ring => vec![],
+            Topology::Full => (0..count).filter(|other| *other != idx).collect(),
+        }
+    }
+
+    /// Copy the [count](MigrationConfig::count) fittest genomes of each island into its
+    /// neighbors, whose most recent generation must already be evaluated. Migrants are chosen
+    /// before any of them move, so that they only travel a single hop.
+    pub fn migrate(&mut self) {
+        let migrants = self
+            .islands
+            .iter()
+            .map(|island| {
+                let mut members = island
+                    .species()
+                    .iter()
+                    .flat_map(|Specie { members, .. }| members.iter())
+                    .collect::<Vec<_>>();
+                members.sort_by(|(_, l), (_, r)| r.total_cmp(l));
+                members.truncate(self.config.count);
+                members.into_iter().cloned().collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+
+        for (idx, migrants) in migrants.into_iter().enumerate() {
+            for neighbor in self.neighbors(idx) {
+                self.islands[neighbor].immigrate(migrants.iter().cloned());
+            }
+        }
+    }
+
+    /// Reproduce every island into its next generation, migrating first if this generation is
+    /// one of every [interval](MigrationConfig::interval). Fails if any island hasn't been
+    /// evaluated yet, or if any island fails to step, in which case no island has stepped nor
+    /// migrated, and nothing but their rngs have changed.
+    pub fn step(&mut self) -> Result<(), EvolutionError> {
+        if let Some(island) = self.islands.iter().find(|i| !i.ask().is_empty()) {
+            return Err(EvolutionError::Unevaluated {
+                pending: island.ask().len(),
+                generation: island.generation(),
+            });
+        }
+
+        let interval = self.config.interval;
+        let evaluated = if interval != 0 && (self.generation() + 1).is_multiple_of(interval) {
+            let evaluated = self
+                .islands
+                .iter()
+                .map(Evolver::evaluated)
+                .collect::<Vec<_>>();
+            self.migrate();
+            Some(evaluated)
+        } else {
+            None
+        };
+
+        let mut innogen = InnoGen::new(self.inno_head());
+        let staged = self
+            .islands
+            .iter_mut()
+            .map(|island| island.stage(&mut innogen))
+            .collect::<Result<Vec<_>, _>>();
+        match staged {
+            Ok(staged) => {
+                for (island, staged) in self.islands.iter_mut().zip(staged) {
+                    island.commit(staged);
+                }
+                Ok(())
+            }
+            Err(err) => {
+                for (island, evaluated) in
+                    self.islands.iter_mut().zip(evaluated.into_iter().flatten())
+                {
+                    island.restore(evaluated);
+                }
+                Err(err)
+            }
+        }
+    }
+
+    /// Stop evolving, and take each island's most recently evaluated species, along with the
+    /// innovation head shared between them
+    pub fn into_parts(self) -> (Vec<Vec<Specie<C, G>>>, usize) {
+        let inno_head = self.inno_head();
+        let species = self
+            .islands
+            .into_iter()
+            .map(|island| island.into_parts().0)
+            .collect();
+        (species, inno_head)
+    }
+}
+
+impl<
+        #[cfg(not(feature = "parallel"))] C: Connection,
+        #[cfg(feature = "parallel")] C: Connection + Sync,
+        #[cfg(not(feature = "parallel"))] G: Genome<C>,
+        #[cfg(feature = "parallel")] G: Genome<C> + Send + Sync,
+        R: RngCore,
+    > Islands<C, G, R>
+{
+    /// [Evaluate](Evolver::eval) every island with `scenario`
+    pub fn eval<
+        #[cfg(not(feature = "parallel"))] S: Scenario<C, G>,
+        #[cfg(feature = "parallel")] S: Scenario<C, G> + Sync,
+    >(
+        &mut self,
+        scenario: &S,
+    ) {
+        for island in self.islands.iter_mut() {
+            island.eval(scenario);
+        }
+    }
+}
+
+impl<C: Connection, G: Genome<C>, R: RngCore + Serialize + for<'de> Deserialize<'de>>
+    Islands<C, G, R>
+{
+    /// Serialize these islands to a JSON string
+    pub fn to_string(&self) -> Result<String, Box<dyn Error>> {
+        Ok(serde_json::to_string(self)?)
+    }
+
+    /// Deserialize islands from a JSON string
+    #[allow(clippy::should_implement_trait)]
+    pub fn from_str(s: &str) -> Result<Self, Box<dyn Error>> {
+        serde_json::from_str(s).map_err(|op| op.into())
+    }
+
+    /// Checkpoint the complete state of every island to a file at `path`
+    pub fn to_file<P: AsRef<Path>>(&self, path: P) -> Result<(), Box<dyn Error>> {
+        fs::write(path, self.to_string()?)?;
+        Ok(())
+    }
+
+    /// Resume evolution from a checkpoint written by [to_file](Islands::to_file)
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self, Box<dyn Error>> {
+        Self::from_str(&fs::read_to_string(path)?)
+    }
+}
+
+/// The island counterpart to [evolve](crate::scenario::evolve), evolving `islands` with
+/// `scenario`. `hook` is called after each generation is evaluated, and may halt evolution by
+/// returning a ControlFlow::Break. Fails if some generation can't be reproduced.
+#[allow(clippy::type_complexity)]
+pub fn evolve_islands<
+    #[cfg(not(feature = "parallel"))] C: Connection,
+    #[cfg(feature = "parallel")] C: Connection + Sync,
+    #[cfg(not(feature = "parallel"))] G: Genome<C>,
+    #[cfg(feature = "parallel")] G: Genome<C> + Send + Sync,
+    #[cfg(not(feature = "parallel"))] S: Scenario<C, G>,
+    #[cfg(feature = "parallel")] S: Scenario<C, G> + Sync,
+    R: RngCore,
+>(
+    scenario: S,
+    mut islands: Islands<C, G, R>,
+    mut hook: impl FnMut(&Islands<C, G, R>) -> ControlFlow<()>,
+) -> Result<(Vec<Vec<Specie<C, G>>>, usize), EvolutionError> {
+    loop {
+        islands.eval(&scenario);
+        if hook(&islands).is_break() {
+            break Ok(islands.into_parts());
+        }
+
+        islands.step()?;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        config::EvolutionConfig,
+        genome::{Recurrent, WConnection},
+        macros::fixtures::WeightSum,
+        population::population_init,
+        random::WyRng,
+        test_t,
+    };
+    use fxhash::FxHashMap;
+
+    type C = WConnection;
+    type G = Recurrent<C>;
+
+    fn islands<T: Genome<C>>(count: usize, config: MigrationConfig) -> Islands<C, T, WyRng> {
+        Islands::new(
+            (0..count)
+                .map(|idx| {
+                    Evolver::new(
+                        population_init::<C, T>(2, 1, 10),
+                        EvolutionConfig::default(),
+                        WyRng::seeded(idx as u64),
+                    )
+                })
+                .collect(),
+            config,
+        )
+    }
+
+    fn population<T: Genome<C>>(island: &Evolver<C, T, WyRng>) -> Vec<f64> {
+        let mut fitness = island
+            .species()
+            .iter()
+            .flat_map(|Specie { members, .. }| members.iter().map(|(_, f)| *f))
+            .collect::<Vec<_>>();
+        fitness.sort_by(f64::total_cmp);
+        fitness
+    }
+
+    test_t!(test_migrate_ring[T: G]() {
+        let mut islands = islands::<T>(3, MigrationConfig {
+            count: 2,
+            topology: Topology::Ring,
+            ..MigrationConfig::default()
+        });
+        for (idx, island) in islands.islands_mut().iter_mut().enumerate() {
+            island.tell((0..10).map(|f| (100 * idx + f) as f64)).unwrap();
+        }
+
+        islands.migrate();
+        let want = [
+            [&[208., 209.][..], &(0..10).map(|f| f as f64).collect::<Vec<_>>()].concat(),
+            [&[8., 9.][..], &(100..110).map(|f| f as f64).collect::<Vec<_>>()].concat(),
+            [&[108., 109.][..], &(200..210).map(|f| f as f64).collect::<Vec<_>>()].concat(),
+        ];
+        for (island, mut want) in islands.islands().iter().zip(want) {
+            want.sort_by(f64::total_cmp);
+            assert_eq!(population(island), want);
+        }
+    });
+
+    test_t!(test_migrate_full[T: G]() {
+        let mut islands = islands::<T>(3, MigrationConfig {
+            count: 1,
+            topology: Topology::Full,
+            ..MigrationConfig::default()
+        });
+        for (idx, island) in islands.islands_mut().iter_mut().enumerate() {
+            island.tell((0..10).map(|f| (100 * idx + f) as f64)).unwrap();
+        }
+
+        islands.migrate();
+        for (idx, island) in islands.islands().iter().enumerate() {
+            let fitness = population(island);
+            assert_eq!(fitness.len(), 12);
+            for other in (0..3).filter(|other| *other != idx) {
+                assert!(fitness.contains(&((100 * other + 9) as f64)));
+            }
+        }
+        assert_eq!(islands.fittest().unwrap().0, 2);
+    });
+
+    test_t!(test_inno_consistent[T: G]() {
+        let islands = islands::<T>(4, MigrationConfig {
+            interval: 3,
+            ..MigrationConfig::default()
+        });
+        let (species, inno_head) = evolve_islands(WeightSum, islands, |islands| {
+            if islands.generation() == 20 {
+                ControlFlow::Break(())
+            } else {
+                ControlFlow::Continue(())
+            }
+        })
+        .unwrap();
+
+        // an innovation id never describes 2 different paths, on any island
+        let mut paths = FxHashMap::default();
+        for (genome, _) in species
+            .iter()
+            .flatten()
+            .flat_map(|Specie { members, .. }| members)
+        {
+            for c in genome.connections() {
+                assert!(c.inno() < inno_head);
+                assert_eq!(*paths.entry(c.inno()).or_insert(c.path()), c.path());
+            }
+        }
+    });
+
+    test_t!(test_islands_step_failed[T: G]() {
+        // genomes without io have nowhere to form a connection, and so can't be mutated
+        let mut islands = Islands::new(
+            vec![
+                Evolver::new(
+                    population_init::<C, T>(2, 1, 10),
+                    EvolutionConfig::default(),
+                    WyRng::seeded(0),
+                ),
+                Evolver::new(
+                    population_init::<C, T>(0, 0, 10),
+                    EvolutionConfig::default(),
+                    WyRng::seeded(1),
+                ),
+            ],
+            MigrationConfig {
+                interval: 1,
+                count: 1,
+                topology: Topology::Ring,
+            },
+        );
+        islands.islands_mut()[0].tell(vec![1.; 10]).unwrap();
+        islands.islands_mut()[1].tell(vec![0.; 10]).unwrap();
+
+        let state = |islands: &Islands<C, T, WyRng>| {
+            let mut state =
+                serde_json::from_str::<serde_json::Value>(&islands.to_string().unwrap()).unwrap();
+            for island in state["islands"].as_array_mut().unwrap() {
+                island.as_object_mut().unwrap().remove("rng");
+            }
+            state
+        };
+        let before = state(&islands);
+        for _ in 0..2 {
+            assert_eq!(islands.step(), Err(EvolutionError::Saturated));
+            assert_eq!(state(&islands), before);
+            assert!(islands.islands().iter().all(|island| island.generation() == 0));
+            assert!(islands.islands().iter().all(|island| population(island).len() == 10));
+        }
+    });
+
+    test_t!(test_islands_resume[T: G]() {
+        let mut islands = islands::<T>(2, MigrationConfig {
+            interval: 2,
+            ..MigrationConfig::default()
+        });
+        assert!(matches!(
+            islands.step(),
+            Err(EvolutionError::Unevaluated { generation: 0, .. })
+        ));
+        for _ in 0..3 {
+            islands.eval(&WeightSum);
+            islands.step().unwrap();
+        }
+
+        let mut resumed = Islands::<C, T, WyRng>::from_str(&islands.to_string().unwrap()).unwrap();
+        for _ in 0..5 {
+            islands.eval(&WeightSum);
+            resumed.eval(&WeightSum);
+            islands.step().unwrap();
+            resumed.step().unwrap();
+        }
+        assert_eq!(islands.generation(), 8);
+        assert_eq!(islands.to_string().unwrap(), resumed.to_string().unwrap());
+    });
+}
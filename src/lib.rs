@@ -3,17 +3,28 @@
 #![allow(incomplete_features)]
 #![allow(mixed_script_confusables)]
 
+pub mod coevolution;
+pub mod config;
 pub mod crossover;
+pub mod elites;
+pub mod error;
 pub mod genome;
+pub mod hall;
+pub mod islands;
 pub mod macros;
 pub mod network;
+pub mod novelty;
+pub mod pareto;
 pub mod population;
 pub mod random;
 pub mod reproduce;
 pub mod scenario;
 pub mod serialize;
 
-pub use genome::{Connection, Genome};
+pub use coevolution::CoevolutionScenario;
+pub use config::EvolutionConfig;
+pub use error::EvolutionError;
+pub use genome::{Connection, Genome, Node};
 pub use network::{activate, Network};
 pub use population::Specie;
-pub use scenario::{Hook, Scenario, Stats};
+pub use scenario::{BehaviorScenario, Evolver, Hook, MultiObjective, Scenario, Stats};
@@ -91,20 +91,24 @@ macro_rules! assert_some_normalized {
 macro_rules! mutate_param {
     ([$($evt:ident),+]: [$($prob:expr),+]) => {
         ::paste::paste! {
-            fn mutate_param(&mut self, rng: &mut impl rand::RngCore) {
+            fn mutate_param(
+                &mut self,
+                rng: &mut impl rand::RngCore,
+                config: &$crate::config::MutationConfig,
+            ) {
                 use $crate::random::EventKind;
                 use rand::Rng;
                 $crate::events!(Param[$($evt),*]);
                 const PARAM_PROBABILITIES: [u64; ParamEvent::COUNT] = [$($prob),*];
 
                 if let Some(evt) = ParamEvent::pick(rng, PARAM_PROBABILITIES) {
-                    let replace = rng.next_u64() < Self::PARAM_REPLACE_PROBABILITY;
+                    let replace = rng.next_u64() < $crate::random::percent(config.param_replace);
                     let v: f64 = rng.sample(rand::distr::Uniform::new_inclusive(-3., 3.).expect("distribution of -3. ..= 3. failed"));
                     match evt {
                         $(ParamEvent::[<$evt:camel>] => self.[<$evt:lower>] = if replace {
                             v
                         } else {
-                            self.[<$evt:lower>] + ( Self::PARAM_PERTURB_FAC * v )
+                            self.[<$evt:lower>] + ( config.param_perturb * v )
                         },)*
                     }
                 }
@@ -167,3 +171,63 @@ macro_rules! events {
         }
     };
 }
+
+/// Scenarios and genomes shared by the tests of several modules
+#[cfg(test)]
+pub(crate) mod fixtures {
+    use crate::{
+        activate::relu,
+        genome::{Connection, Genome, WConnection},
+        scenario::{BehaviorScenario, Scenario},
+    };
+
+    /// Genomes who differ only in the weight of their first connection, each paired with its
+    /// weight
+    pub(crate) fn weighted<G: Genome<WConnection>>(
+        weights: impl IntoIterator<Item = f64>,
+    ) -> Vec<(G, f64)> {
+        weights
+            .into_iter()
+            .map(|weight| {
+                let (mut genome, _) = G::new(2, 1);
+                genome.connections_mut()[0].weight = weight;
+                (genome, weight)
+            })
+            .collect()
+    }
+
+    /// Sum of the weights of enabled connections
+    pub(crate) struct WeightSum;
+
+    impl<C: Connection, G: Genome<C>> Scenario<C, G> for WeightSum {
+        fn io(&self) -> (usize, usize) {
+            (2, 1)
+        }
+
+        fn eval(&self, genome: &G) -> f64 {
+            genome
+                .connections()
+                .iter()
+                .filter(|c| c.enabled())
+                .map(|c| relu(c.weight()))
+                .sum()
+        }
+    }
+
+    /// Weight sum, behaving as the number of enabled connections and nodes
+    pub(crate) struct WeightShape;
+
+    impl<C: Connection, G: Genome<C>> BehaviorScenario<C, G> for WeightShape {
+        fn io(&self) -> (usize, usize) {
+            (2, 1)
+        }
+
+        fn eval(&self, genome: &G) -> (f64, Vec<f64>) {
+            let enabled = genome.connections().iter().filter(|c| c.enabled());
+            (
+                enabled.clone().map(|c| relu(c.weight())).sum(),
+                vec![enabled.count() as f64, genome.nodes().len() as f64],
+            )
+        }
+    }
+}
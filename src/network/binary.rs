@@ -0,0 +1,266 @@
+use super::{FromGenome, Recurrent, Stateful};
+use crate::{Connection, Genome, Network};
+use serde::{Deserialize, Serialize};
+
+const WORD: usize = u64::BITS as usize;
+
+/// A stateful, binarized NN, for when inference needs to be very cheap. Every weight and every
+/// neuron is quantized to its sign, ±1, and packed into bit vectors, so that the pre-activation
+/// of a neuron is found with an XNOR and a popcount rather than by multiplying floats:
+///
+/// `2 * popcount(!(state ^ sign) & mask) - popcount(mask)`
+///
+/// Where `sign` holds the sign of each incoming weight, and `mask` which weights are present.
+/// A neuron is +1 when its pre-activation is strictly positive, and -1 otherwise. Sensory input
+/// is quantized in the same way, static neurons are always +1, and node activations are ignored.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Binary {
+    /// Number of neurons
+    size: usize,
+    /// Number of words needed to hold a bit for every neuron
+    words: usize,
+    /// Packed state of neurons 0-N, where a set bit is +1
+    state: Vec<u64>,
+    /// Neurons who are always +1
+    statics: Vec<u64>,
+    /// Neurons whose state isn't computed, being static or taking input
+    fixed: Vec<u64>,
+    /// Nd signs of weights between neurons, one row of words per neuron, indexed as [to, from]
+    sign: Vec<u64>,
+    /// Nd presence of weights between neurons, laid out like sign
+    mask: Vec<u64>,
+    /// Range of input neurons, indexing into state
+    sensory: (usize, usize),
+    /// Range of output neurons, indexing into state
+    action: (usize, usize),
+    /// Most recent state of output neurons, as ±1
+    output: Vec<f64>,
+}
+
+impl Binary {
+    /// Force static and sensory neurons back to their fixed values
+    fn pin(&self, state: &mut [u64], input: &[u64]) {
+        for (i, word) in state.iter_mut().enumerate() {
+            *word = (*word & !self.fixed[i]) | self.statics[i] | input[i];
+        }
+    }
+
+    fn bit(bits: &[u64], idx: usize) -> bool {
+        bits[idx / WORD] & (1 << (idx % WORD)) != 0
+    }
+
+    fn set(bits: &mut [u64], idx: usize) {
+        bits[idx / WORD] |= 1 << (idx % WORD);
+    }
+
+    fn sync_output(&mut self) {
+        for (i, v) in (self.action.0..self.action.1).zip(self.output.iter_mut()) {
+            *v = if Self::bit(&self.state, i) { 1. } else { -1. };
+        }
+    }
+}
+
+impl Network for Binary {
+    fn step(&mut self, prec: usize, input: &[f64]) {
+        debug_assert!(input.len() == self.sensory.1 - self.sensory.0);
+        let mut m_input = vec![0; self.words];
+        for (i, v) in input.iter().enumerate() {
+            if *v > 0. {
+                Self::set(&mut m_input, self.sensory.0 + i);
+            }
+        }
+
+        let mut state = self.state.clone();
+        self.pin(&mut state, &m_input);
+        for _ in 0..prec {
+            let mut next = vec![0; self.words];
+            for node in 0..self.size {
+                let row = node * self.words..(node + 1) * self.words;
+                let (agree, total) = state
+                    .iter()
+                    .zip(&self.sign[row.clone()])
+                    .zip(&self.mask[row])
+                    .fold((0, 0), |(agree, total), ((s, w), m)| {
+                        (agree + (!(s ^ w) & m).count_ones(), total + m.count_ones())
+                    });
+
+                if 2 * agree > total {
+                    Self::set(&mut next, node);
+                }
+            }
+
+            self.pin(&mut next, &m_input);
+            state = next;
+        }
+
+        self.state = state;
+        self.sync_output();
+    }
+
+    fn flush(&mut self) {
+        self.state = self.statics.clone();
+        self.sync_output();
+    }
+
+    fn output(&self) -> &[f64] {
+        &self.output
+    }
+}
+
+impl Recurrent for Binary {}
+
+impl Stateful for Binary {}
+
+impl<C: Connection, G: Genome<C>> FromGenome<C, G> for Binary {
+    fn from_genome(genome: &G) -> Self {
+        let size = genome.nodes().len();
+        let words = size.div_ceil(WORD);
+
+        let mut statics = vec![0; words];
+        let mut fixed = vec![0; words];
+        Self::set(&mut statics, genome.static_node());
+        Self::set(&mut fixed, genome.static_node());
+        for i in genome.sensory() {
+            Self::set(&mut fixed, i);
+        }
+
+        let mut sign = vec![0; size * words];
+        let mut mask = vec![0; size * words];
+        for c in genome
+            .connections()
+            .iter()
+            .filter(|c| c.enabled() && c.weight() != 0.)
+        {
+            let row = c.to() * words;
+            Self::set(&mut mask[row..row + words], c.from());
+            if c.weight() > 0. {
+                Self::set(&mut sign[row..row + words], c.from());
+            } else {
+                sign[row + c.from() / WORD] &= !(1 << (c.from() % WORD));
+            }
+        }
+
+        let mut nn = Self {
+            size,
+            words,
+            state: statics.clone(),
+            statics,
+            fixed,
+            sign,
+            mask,
+            sensory: (genome.sensory().start, genome.sensory().end),
+            action: (genome.action().start, genome.action().end),
+            output: vec![-1.; genome.action().len()],
+        };
+        nn.sync_output();
+        nn
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        config::MutationConfig,
+        genome::{InnoGen, Recurrent, WConnection},
+        random::default_rng,
+    };
+    use rand::Rng;
+
+    type C = WConnection;
+
+    /// Binarized reference network, stepped with floats
+    fn step_reference(genome: &Recurrent<C>, state: &mut Vec<f64>, prec: usize, input: &[f64]) {
+        let pin = |state: &mut Vec<f64>| {
+            state[genome.static_node()] = 1.;
+            for (i, v) in genome.sensory().zip(input) {
+                state[i] = if *v > 0. { 1. } else { -1. };
+            }
+        };
+
+        pin(state);
+        for _ in 0..prec {
+            let mut next = vec![0.; state.len()];
+            for c in genome
+                .connections()
+                .iter()
+                .filter(|c| c.enabled() && c.weight() != 0.)
+            {
+                next[c.to()] += state[c.from()] * c.weight().signum();
+            }
+            *state = next
+                .into_iter()
+                .map(|v| if v > 0. { 1. } else { -1. })
+                .collect();
+            pin(state);
+        }
+    }
+
+    #[test]
+    fn test_majority() {
+        let (mut genome, _) = Recurrent::<C>::new(3, 1);
+        for c in genome.connections_mut() {
+            c.weight = if c.from() == 4 { 0. } else { 1. };
+        }
+
+        let mut nn = Binary::from_genome(&genome);
+        for (input, want) in [
+            ([0., 0., 0.], -1.),
+            ([1., 0., 0.], -1.),
+            ([1., 0., 1.], 1.),
+            ([0., 1., 1.], 1.),
+            ([1., 1., 1.], 1.),
+            ([-1., 1., -1.], -1.),
+        ] {
+            nn.step(1, &input);
+            assert_eq!(nn.output(), [want], "{input:?}");
+        }
+    }
+
+    #[test]
+    fn test_matches_reference() {
+        let mut rng = default_rng();
+        let config = MutationConfig::default();
+        for _ in 0..20 {
+            let (mut genome, inno_head) = Recurrent::<C>::new(4, 3);
+            let mut inno = InnoGen::new(inno_head);
+            for _ in 0..rng.random_range(10..100) {
+                let _ = genome.mutate(&mut rng, &mut inno, &config);
+            }
+
+            let mut nn = Binary::from_genome(&genome);
+            let mut state = vec![-1.; genome.nodes().len()];
+            for prec in 1..4 {
+                let input = (0..4)
+                    .map(|_| rng.random_range(-1. ..1.))
+                    .collect::<Vec<_>>();
+                nn.step(prec, &input);
+                step_reference(&genome, &mut state, prec, &input);
+                assert_eq!(nn.output(), &state[genome.action()]);
+            }
+        }
+    }
+
+    #[test]
+    fn test_serialization_equivalence() {
+        let mut rng = default_rng();
+        let (mut genome, inno_head) = Recurrent::<C>::new(4, 3);
+        let mut inno = InnoGen::new(inno_head);
+        for _ in 0..50 {
+            let _ = genome.mutate(&mut rng, &mut inno, &MutationConfig::default());
+        }
+
+        let mut nn = Binary::from_genome(&genome);
+        nn.step(2, &[1., -1., 1., 1.]);
+        let mut deserialized = Binary::from_str(&nn.to_string().unwrap()).unwrap();
+        assert_eq!(nn.output(), deserialized.output());
+        for _ in 0..10 {
+            let input = (0..4)
+                .map(|_| rng.random_range(-1. ..1.))
+                .collect::<Vec<_>>();
+            nn.step(2, &input);
+            deserialized.step(2, &input);
+            assert_eq!(nn.output(), deserialized.output());
+        }
+    }
+}
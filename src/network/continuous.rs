@@ -1,10 +1,11 @@
 use super::{FromGenome, Recurrent, Stateful};
 use crate::{
-    genome::NodeKind,
+    activate::Activation,
+    genome::Node,
     serialize::{deserialize_matrix_flat, deserialize_matrix_square, serialize_matrix},
     Connection, Genome, Network,
 };
-use rulinalg::matrix::{BaseMatrix, BaseMatrixMut, Matrix};
+use rulinalg::matrix::{BaseMatrix, Matrix};
 use serde::{Deserialize, Serialize};
 
 /// A stateful NN who receives input continuously, useful for realtime problems
@@ -14,49 +15,244 @@ use serde::{Deserialize, Serialize};
 /// on the dynamics of small continuous-time recurrent neural networks (beer 1995)
 /// and with some code stolen from [TLmaK0's neat implentation](https://github.com/TLmaK0/rustneat)
 #[derive(Debug, Serialize, Deserialize)]
+#[serde(from = "SavedContinuous")]
 pub struct Continuous {
     /// 1d state of neurons 0-N
-    #[serde(
-        serialize_with = "serialize_matrix",
-        deserialize_with = "deserialize_matrix_flat"
-    )]
+    #[serde(serialize_with = "serialize_matrix")]
     pub y: Matrix<f64>,
     /// 1d bias of neurons 0-N
-    #[serde(
-        serialize_with = "serialize_matrix",
-        deserialize_with = "deserialize_matrix_flat"
-    )]
+    #[serde(serialize_with = "serialize_matrix")]
     pub θ: Matrix<f64>,
     /// 1d membrane resistance time constant
-    #[serde(
-        serialize_with = "serialize_matrix",
-        deserialize_with = "deserialize_matrix_flat"
-    )]
+    #[serde(serialize_with = "serialize_matrix")]
     pub τ: Matrix<f64>,
+    /// 1d activation of neurons 0-N. Networks saved before neurons had their own activation
+    /// load with the default activation on every neuron.
+    pub σ: Vec<Activation>,
     /// Nd weights between neurons, indexed as [from, to]
-    #[serde(
-        serialize_with = "serialize_matrix",
-        deserialize_with = "deserialize_matrix_square"
-    )]
+    #[serde(serialize_with = "serialize_matrix")]
     pub w: Matrix<f64>,
     /// Range of input neurons, indexing into y
     pub sensory: (usize, usize),
     /// Range of output neurons, indexing into y
     pub action: (usize, usize),
+    /// How y is integrated through time on each step
+    pub integrator: Integrator,
 }
 
-impl Network for Continuous {
-    fn step<F: Fn(f64) -> f64>(&mut self, prec: usize, input: &[f64], σ: F) {
-        let mut m_input = Matrix::zeros(1, self.y.cols());
-        m_input.mut_data()[self.sensory.0..self.sensory.1].copy_from_slice(input);
+/// A [Continuous] as it may have been saved, whose activations might not have been
+#[derive(Deserialize)]
+struct SavedContinuous {
+    #[serde(deserialize_with = "deserialize_matrix_flat")]
+    y: Matrix<f64>,
+    #[serde(deserialize_with = "deserialize_matrix_flat")]
+    θ: Matrix<f64>,
+    #[serde(deserialize_with = "deserialize_matrix_flat")]
+    τ: Matrix<f64>,
+    #[serde(default)]
+    σ: Option<Vec<Activation>>,
+    #[serde(deserialize_with = "deserialize_matrix_square")]
+    w: Matrix<f64>,
+    sensory: (usize, usize),
+    action: (usize, usize),
+    #[serde(default)]
+    integrator: Integrator,
+}
+
+impl From<SavedContinuous> for Continuous {
+    fn from(saved: SavedContinuous) -> Self {
+        Self {
+            σ: saved
+                .σ
+                .unwrap_or_else(|| vec![Activation::default(); saved.θ.cols()]),
+            y: saved.y,
+            θ: saved.θ,
+            τ: saved.τ,
+            w: saved.w,
+            sensory: saved.sensory,
+            action: saved.action,
+            integrator: saved.integrator,
+        }
+    }
+}
 
+/// Numerical method by which a [Continuous] network integrates its state. Each call to
+/// [step](Network::step) advances the network by one unit of time, in `prec` steps of `1 / prec`
+/// ( or, for [Rk45](Integrator::Rk45), in however many steps its tolerance needs ).
+#[derive(Debug, Default, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum Integrator {
+    /// Forward euler, cheap but prone to instability for stiff networks, whose weights or time
+    /// constants are large relative to their step size
+    #[default]
+    Euler,
+    /// Classic 4th order runge-kutta, with fixed steps
+    Rk4,
+    /// Dormand-Prince runge-kutta 5(4), with adaptive steps. The first step is `1 / prec`, after
+    /// which steps shrink or grow to keep the estimated error of every neuron within `tolerance`.
+    /// Past [RK45_MAX_STEPS] attempts in a single unit of time, the rest of it is taken in
+    /// fixed steps of `1 / prec`.
+    Rk45 { tolerance: f64 },
+}
+
+/// Butcher tableau of dormand-prince 5(4), where row i weighs the derivatives of stages before it
+const DP_A: [[f64; 6]; 7] = [
+    [0., 0., 0., 0., 0., 0.],
+    [1. / 5., 0., 0., 0., 0., 0.],
+    [3. / 40., 9. / 40., 0., 0., 0., 0.],
+    [44. / 45., -56. / 15., 32. / 9., 0., 0., 0.],
+    [
+        19372. / 6561.,
+        -25360. / 2187.,
+        64448. / 6561.,
+        -212. / 729.,
+        0.,
+        0.,
+    ],
+    [
+        9017. / 3168.,
+        -355. / 33.,
+        46732. / 5247.,
+        49. / 176.,
+        -5103. / 18656.,
+        0.,
+    ],
+    [
+        35. / 384.,
+        0.,
+        500. / 1113.,
+        125. / 192.,
+        -2187. / 6784.,
+        11. / 84.,
+    ],
+];
+
+/// Difference between the 5th and 4th order solution weights of dormand-prince 5(4)
+const DP_E: [f64; 7] = [
+    71. / 57600.,
+    0.,
+    -71. / 16695.,
+    71. / 1920.,
+    -17253. / 339200.,
+    22. / 525.,
+    -1. / 40.,
+];
+
+/// Smallest step that Rk45 will take, accepting it regardless of its error unless it's NaN
+const RK45_MIN_STEP: f64 = 1e-9;
+
+/// Most steps, accepted or not, that Rk45 will attempt before falling back to fixed steps
+pub const RK45_MAX_STEPS: usize = 10_000;
+
+impl Integrator {
+    /// Integrate y over one unit of time, where `f(y, dy)` writes dy/dt into dy
+    pub fn integrate(&self, y: &mut [f64], prec: usize, mut f: impl FnMut(&[f64], &mut [f64])) {
+        let size = y.len();
         let inv = 1. / (prec as f64);
-        for _ in 0..prec {
-            self.y += (((&self.y + &self.θ).apply(&σ) * &self.w) - &self.y + &m_input)
-                .elemul(&self.τ)
-                .apply(&|v| v * inv);
+        match *self {
+            Self::Euler => {
+                let mut dy = vec![0.; size];
+                for _ in 0..prec {
+                    f(y, &mut dy);
+                    for (y, dy) in y.iter_mut().zip(&dy) {
+                        *y += dy * inv;
+                    }
+                }
+            }
+            Self::Rk4 => {
+                let mut k = [(); 4].map(|_| vec![0.; size]);
+                let mut at = vec![0.; size];
+                for _ in 0..prec {
+                    f(y, &mut k[0]);
+                    for (stage, scale) in [(1, 0.5), (2, 0.5), (3, 1.)] {
+                        for ((at, y), k) in at.iter_mut().zip(y.iter()).zip(&k[stage - 1]) {
+                            *at = y + k * inv * scale;
+                        }
+                        f(&at, &mut k[stage]);
+                    }
+                    for (i, y) in y.iter_mut().enumerate() {
+                        *y += (k[0][i] + 2. * k[1][i] + 2. * k[2][i] + k[3][i]) * inv / 6.;
+                    }
+                }
+            }
+            Self::Rk45 { tolerance } => {
+                let mut k = [(); 7].map(|_| vec![0.; size]);
+                let mut at = vec![0.; size];
+                let mut h = inv;
+                let mut t = 0.;
+                let mut attempts = 0;
+                while t < 1. {
+                    // too stiff to finish adaptively, so the rest is taken in fixed steps
+                    let fixed = attempts >= RK45_MAX_STEPS;
+                    if fixed {
+                        h = inv;
+                    }
+                    attempts += 1;
+                    h = h.min(1. - t);
+                    f(y, &mut k[0]);
+                    for stage in 1..7 {
+                        for (i, at) in at.iter_mut().enumerate() {
+                            *at = y[i]
+                                + h * (0..stage).fold(0., |acc, j| acc + DP_A[stage][j] * k[j][i]);
+                        }
+                        f(&at, &mut k[stage]);
+                    }
+
+                    // f64::max would drop a NaN error, and so accept the step
+                    let err = (0..size).fold(0., |acc: f64, i| {
+                        let err = (h * (0..7).fold(0., |e, j| e + DP_E[j] * k[j][i])).abs();
+                        if err.is_nan() || err > acc {
+                            err
+                        } else {
+                            acc
+                        }
+                    });
+                    if fixed || err <= tolerance || (h <= RK45_MIN_STEP && !err.is_nan()) {
+                        // the last stage is evaluated at the 5th order solution
+                        y.copy_from_slice(&at);
+                        t += h;
+                    }
+
+                    h = if err.is_nan() {
+                        h * 0.2
+                    } else if err == 0. {
+                        h * 5.
+                    } else {
+                        h * (0.9 * (tolerance / err).powf(0.2)).clamp(0.2, 5.)
+                    }
+                    .max(RK45_MIN_STEP);
+                }
+            }
         }
     }
+}
+
+impl Network for Continuous {
+    fn step(&mut self, prec: usize, input: &[f64]) {
+        let mut m_input = vec![0.; self.y.cols()];
+        m_input[self.sensory.0..self.sensory.1].copy_from_slice(input);
+
+        let (θ, τ, σ, w) = (&self.θ, &self.τ, &self.σ, &self.w);
+        self.integrator.integrate(self.y.mut_data(), prec, |y, dy| {
+            let activated = Matrix::new(
+                1,
+                y.len(),
+                y.iter()
+                    .zip(θ.data())
+                    .zip(σ)
+                    .map(|((y, θ), σ)| σ.apply(y + θ))
+                    .collect::<Vec<_>>(),
+            );
+            for ((((dy, s), y), i), τ) in dy
+                .iter_mut()
+                .zip((activated * w).data())
+                .zip(y)
+                .zip(&m_input)
+                .zip(τ.data())
+            {
+                *dy = (s - y + i) * τ;
+            }
+        });
+    }
 
     fn flush(&mut self) {
         self.y = Matrix::zeros(1, self.y.cols());
@@ -79,19 +275,14 @@ impl<C: Connection, G: Genome<C>> FromGenome<C, G> for Continuous {
             θ: Matrix::new(
                 1,
                 cols,
-                genome
-                    .nodes()
-                    .iter()
-                    .map(|n| {
-                        if matches!(n, NodeKind::Static) {
-                            1.
-                        } else {
-                            0.
-                        }
-                    })
-                    .collect::<Vec<_>>(),
+                genome.nodes().iter().map(Node::bias).collect::<Vec<_>>(),
             ),
-            τ: Matrix::new(1, cols, vec![0.1; cols]),
+            τ: Matrix::new(
+                1,
+                cols,
+                genome.nodes().iter().map(Node::tau).collect::<Vec<_>>(),
+            ),
+            σ: genome.nodes().iter().map(Node::activation).collect(),
             w: {
                 let mut w = vec![0.; cols * cols];
                 for c in genome.connections().iter().filter(|c| c.enabled()) {
@@ -101,6 +292,7 @@ impl<C: Connection, G: Genome<C>> FromGenome<C, G> for Continuous {
             },
             sensory: (genome.sensory().start, genome.sensory().end),
             action: (genome.action().start, genome.action().end),
+            integrator: Integrator::default(),
         }
     }
 }
@@ -109,11 +301,13 @@ impl<C: Connection, G: Genome<C>> FromGenome<C, G> for Continuous {
 mod test {
     use super::*;
     use crate::{
-        activate, assert_f64_approx, assert_matrix_approx,
+        assert_f64_approx, assert_matrix_approx,
+        config::MutationConfig,
         genome::InnoGen,
-        genome::{self, NodeKind, WConnection},
+        genome::{self, WConnection},
         random::default_rng,
     };
+    use core::cmp::Ordering;
     use rand_distr::{num_traits::Float, Distribution, Uniform};
     use rulinalg::matrix::Matrix;
 
@@ -144,9 +338,11 @@ mod test {
             y: Matrix::new(1, n_neurons, y_data),
             θ: Matrix::new(1, n_neurons, theta_data),
             τ: Matrix::new(1, n_neurons, tau_data),
+            σ: vec![Activation::Sigmoid; n_neurons],
             w: Matrix::new(n_neurons, n_neurons, w_data),
             sensory: (0, 2),
             action: (3, 5),
+            integrator: Integrator::default(),
         };
 
         let serialized = original.to_string().expect("Failed to serialize");
@@ -187,9 +383,11 @@ mod test {
             y: Matrix::new(1, n_neurons, y_data),
             θ: Matrix::new(1, n_neurons, θ_data),
             τ: Matrix::new(1, n_neurons, τ_data),
+            σ: vec![Activation::Sigmoid; n_neurons],
             w: Matrix::new(n_neurons, n_neurons, w_data),
             sensory: (0, 2),
             action: (3, 5),
+            integrator: Integrator::default(),
         };
 
         let mut deserialized =
@@ -202,8 +400,8 @@ mod test {
         for __ in 0..n_steps {
             let input: Vec<f64> = (0..2).map(|_| dist.sample(&mut rng)).collect();
 
-            original.step(precision, &input, activate::steep_sigmoid);
-            deserialized.step(precision, &input, activate::steep_sigmoid);
+            original.step(precision, &input);
+            deserialized.step(precision, &input);
 
             let original_output = original.output();
             let deserialized_output = deserialized.output();
@@ -221,8 +419,19 @@ mod test {
         genome.push_connection(C::new(0, 3, &mut inno));
         genome.push_connection(C::new(0, 1, &mut inno));
         genome.push_connection(C::new(0, 1, &mut inno));
+        genome.nodes_mut()[3].set_activation(Activation::Tanh);
 
         let nn = Continuous::from_genome(&genome);
+        assert_eq!(
+            nn.σ,
+            [
+                Activation::Sigmoid,
+                Activation::Sigmoid,
+                Activation::Sigmoid,
+                Activation::Tanh,
+                Activation::Sigmoid
+            ]
+        );
         unsafe {
             for c in genome.connections() {
                 if c.enabled() {
@@ -230,29 +439,148 @@ mod test {
                 }
             }
 
-            for (i, node) in genome.nodes().iter().enumerate() {
-                assert_f64_approx!(
-                    nn.θ.get_unchecked([0, i]),
-                    if matches!(node, NodeKind::Static) {
-                        1.
-                    } else {
-                        0.
-                    }
-                )
+            for i in 0..genome.nodes().len() {
+                assert_f64_approx!(nn.θ.get_unchecked([0, i]), genome.nodes()[i].bias());
+                assert_f64_approx!(nn.τ.get_unchecked([0, i]), genome.nodes()[i].tau());
             }
         }
 
-        for i in nn.sensory.0..nn.sensory.1 {
-            assert!(genome
-                .nodes()
-                .get(i)
-                .is_some_and(|n| matches!(n, NodeKind::Sensory)))
+        assert_eq!(nn.sensory, (genome.sensory().start, genome.sensory().end));
+        assert_eq!(nn.action, (genome.action().start, genome.action().end));
+    }
+
+    #[test]
+    fn test_evolved_tau() {
+        type C = WConnection;
+
+        let mut rng = default_rng();
+        let config = MutationConfig {
+            node_rate: 100,
+            ..MutationConfig::default()
+        };
+        let (mut genome, _) = genome::Recurrent::<C>::new(2, 2);
+        while genome.action().all(|i| genome.nodes()[i].tau() == 0.1) {
+            genome.mutate_node(&mut rng, &config);
+        }
+
+        let genome = genome::Recurrent::<C>::from_str(&genome.to_string().unwrap()).unwrap();
+        let child = genome.reproduce_with(&genome.clone(), Ordering::Greater, &mut rng);
+        assert_eq!(child.nodes(), genome.nodes());
+
+        let nn = Continuous::from_genome(&child);
+        assert_eq!(
+            nn.τ.data(),
+            &child.nodes().iter().map(Node::tau).collect::<Vec<_>>()
+        );
+        assert!(nn.τ.data().iter().any(|t| *t != 0.1));
+    }
+
+    /// Sensory neuron 0 feeding action neuron 1 with weight `w`, where both activate linearly.
+    /// With constant input i, y0 = i(1 - e^(-a t)) and y1 = wi(1 - (be^(-a t) - ae^(-b t)) / (b - a))
+    fn cascade(a: f64, b: f64, w: f64, integrator: Integrator) -> Continuous {
+        Continuous {
+            y: Matrix::zeros(1, 2),
+            θ: Matrix::zeros(1, 2),
+            τ: Matrix::new(1, 2, vec![a, b]),
+            σ: vec![Activation::Identity; 2],
+            w: Matrix::new(2, 2, vec![0., w, 0., 0.]),
+            sensory: (0, 1),
+            action: (1, 2),
+            integrator,
+        }
+    }
+
+    /// Largest error of a cascade against its analytic solution over 10 steps
+    fn cascade_error(integrator: Integrator, prec: usize) -> f64 {
+        let (a, b, w, i) = (0.5, 0.9, 2., 1.);
+        let mut nn = cascade(a, b, w, integrator);
+        (1..=10).fold(0., |err: f64, t| {
+            nn.step(prec, &[i]);
+            let t = t as f64;
+            let want = w * i * (1. - (b * (-a * t).exp() - a * (-b * t).exp()) / (b - a));
+            err.max((nn.output()[0] - want).abs())
+        })
+    }
+
+    #[test]
+    fn test_integrator_analytic() {
+        let euler = cascade_error(Integrator::Euler, 10);
+        let rk4 = cascade_error(Integrator::Rk4, 10);
+        let rk45 = cascade_error(Integrator::Rk45 { tolerance: 1e-10 }, 10);
+        assert!(euler < 1e-1, "{euler}");
+        assert!(rk4 < 1e-6, "{rk4}");
+        assert!(rk45 < 1e-8, "{rk45}");
+        assert!(rk4 < euler);
+        assert!(cascade_error(Integrator::Euler, 100) < euler);
+    }
+
+    #[test]
+    fn test_integrator_stiff() {
+        // a single neuron strongly inhibiting itself, where y = i(1 - e^(-51t)) / 51
+        let stiff = |integrator| Continuous {
+            y: Matrix::zeros(1, 1),
+            θ: Matrix::zeros(1, 1),
+            τ: Matrix::new(1, 1, vec![1.]),
+            σ: vec![Activation::Identity],
+            w: Matrix::new(1, 1, vec![-50.]),
+            sensory: (0, 1),
+            action: (0, 1),
+            integrator,
+        };
+
+        let mut euler = stiff(Integrator::Euler);
+        let mut rk4 = stiff(Integrator::Rk4);
+        let mut rk45 = stiff(Integrator::Rk45 { tolerance: 1e-8 });
+        for t in 1..=20 {
+            for nn in [&mut euler, &mut rk4, &mut rk45] {
+                nn.step(10, &[1.]);
+            }
+            let want = (1. - (-51. * t as f64).exp()) / 51.;
+            assert!((rk45.output()[0] - want).abs() < 1e-6);
         }
-        for i in nn.action.0..nn.action.1 {
-            assert!(genome
-                .nodes()
-                .get(i)
-                .is_some_and(|n| matches!(n, NodeKind::Action)))
+        // unstable methods blow up, possibly to inf or NaN
+        for nn in [euler, rk4] {
+            assert!(nn.output()[0].abs() >= 1. || nn.output()[0].is_nan());
         }
     }
+
+    #[test]
+    fn test_integrator_rk45_bounded() {
+        let integrate = |y0: f64, f: fn(&[f64], &mut [f64])| {
+            let mut y = [y0];
+            let mut calls = 0;
+            Integrator::Rk45 { tolerance: 1e-8 }.integrate(&mut y, 10, |y, dy| {
+                calls += 1;
+                f(y, dy)
+            });
+            (y[0], calls)
+        };
+
+        // a NaN error is never accepted adaptively, but neither does it loop forever
+        let (y, calls) = integrate(1., |_, dy| dy[0] = f64::NAN);
+        assert!(y.is_nan());
+        assert!(calls >= 7 * RK45_MAX_STEPS);
+        // fixed steps of 0.1 may leave a sliver of time to one more step
+        assert!(calls <= 7 * (RK45_MAX_STEPS + 11));
+
+        // only a NaN error in some stage, which a max from 0 would have hidden
+        let (y, _) = integrate(1., |y, dy| dy[0] = if y[0] > 1. { f64::NAN } else { 1. });
+        assert!(y.is_nan() || y <= 1.);
+
+        // far too stiff to settle on a step within tolerance
+        let (_, calls) = integrate(1., |y, dy| dy[0] = -1e12 * y[0]);
+        assert!(calls <= 7 * (RK45_MAX_STEPS + 11));
+    }
+
+    #[test]
+    fn test_integrator_serialization() {
+        let original = cascade(0.5, 0.9, 2., Integrator::Rk45 { tolerance: 1e-4 });
+        let deserialized = Continuous::from_str(&original.to_string().unwrap()).unwrap();
+        assert_eq!(original.integrator, deserialized.integrator);
+
+        // saved before networks had an integrator, or an activation for each neuron
+        let legacy = Continuous::from_file("benches/data/ctrnn-rand-100.json").unwrap();
+        assert_eq!(legacy.integrator, Integrator::Euler);
+        assert_eq!(legacy.σ, vec![Activation::default(); legacy.θ.cols()]);
+    }
 }
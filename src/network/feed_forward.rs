@@ -0,0 +1,194 @@
+use super::{FromGenome, Linear, Network, Stateless};
+use crate::{
+    activate::Activation,
+    genome::{self, Node},
+    Connection, Genome,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+
+/// A network for acyclic genomes, who evaluates every node exactly once per step. Nodes are
+/// topologically sorted when the network is built, so a single pass in that order is all that's
+/// needed to propagate input through to output, regardless of `prec`.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct FeedForward {
+    /// Non-sensory nodes, in the order that they're evaluated
+    order: Vec<usize>,
+    /// Enabled (from, weight) connections into each node
+    incoming: Vec<Vec<(usize, f64)>>,
+    /// Activation of each node
+    activation: Vec<Activation>,
+    /// Bias of each node, added to its input before activation
+    bias: Vec<f64>,
+    state: Vec<f64>,
+    /// Index of the static node, never evaluated and always holding its bias
+    static_node: usize,
+    /// Range of input neurons, indexing into state
+    sensory: (usize, usize),
+    /// Range of output neurons, indexing into state
+    action: (usize, usize),
+}
+
+impl Network for FeedForward {
+    fn step(&mut self, _: usize, input: &[f64]) {
+        debug_assert!(input.len() == self.sensory.1 - self.sensory.0);
+        self.state[self.sensory.0..self.sensory.1].copy_from_slice(input);
+        for &node in self.order.iter() {
+            self.state[node] = self.activation[node].apply(
+                self.incoming[node]
+                    .iter()
+                    .map(|(from, weight)| self.state[*from] * weight)
+                    .sum::<f64>()
+                    + self.bias[node],
+            );
+        }
+    }
+
+    fn flush(&mut self) {
+        self.state.fill(0.);
+        self.state[self.static_node] = self.bias[self.static_node];
+    }
+
+    fn output(&self) -> &[f64] {
+        &self.state[self.action.0..self.action.1]
+    }
+}
+
+impl Linear for FeedForward {}
+
+impl Stateless for FeedForward {}
+
+impl<C: Connection, N: Node> FromGenome<C, genome::FeedForward<C, N>> for FeedForward {
+    fn from_genome(genome: &genome::FeedForward<C, N>) -> Self {
+        let size = genome.nodes().len();
+        let mut incoming = vec![Vec::new(); size];
+        let mut outgoing = vec![Vec::new(); size];
+        for c in genome.connections().iter().filter(|c| c.enabled()) {
+            incoming[c.to()].push((c.from(), c.weight()));
+            outgoing[c.from()].push(c.to());
+        }
+
+        let mut indegree = incoming.iter().map(Vec::len).collect::<Vec<_>>();
+        let mut ready = (0..size)
+            .filter(|node| indegree[*node] == 0)
+            .collect::<VecDeque<_>>();
+        let mut order = Vec::with_capacity(size);
+        while let Some(node) = ready.pop_front() {
+            order.push(node);
+            for to in outgoing[node].iter() {
+                indegree[*to] -= 1;
+                if indegree[*to] == 0 {
+                    ready.push_back(*to);
+                }
+            }
+        }
+        debug_assert_eq!(order.len(), size, "genome is not acyclic");
+        order.retain(|node| !genome.sensory().contains(node) && *node != genome.static_node());
+
+        let mut nn = Self {
+            order,
+            incoming,
+            activation: genome.nodes().iter().map(Node::activation).collect(),
+            bias: genome.nodes().iter().map(Node::bias).collect(),
+            state: vec![0.; size],
+            static_node: genome.static_node(),
+            sensory: (genome.sensory().start, genome.sensory().end),
+            action: (genome.action().start, genome.action().end),
+        };
+        nn.flush();
+        nn
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::genome::{InnoGen, Neuron, WConnection};
+
+    type C = WConnection;
+
+    fn xor_genome() -> genome::FeedForward<C> {
+        let (mut genome, inno_head) = genome::FeedForward::<C>::new(2, 1);
+        let mut inno = InnoGen::new(inno_head);
+        for c in genome.connections_mut() {
+            c.disable();
+        }
+
+        genome.push_node(Neuron::default());
+        genome.push_node(Neuron::default());
+        for node in 2..6 {
+            genome.nodes_mut()[node].set_activation(Activation::Relu);
+        }
+        for (from, to, weight) in [
+            (4, 2, 1.),
+            (5, 2, -2.),
+            (0, 4, 1.),
+            (1, 4, 1.),
+            (0, 5, 1.),
+            (1, 5, 1.),
+            (3, 5, -1.),
+        ] {
+            let mut c = C::new(from, to, &mut inno);
+            c.weight = weight;
+            genome.push_connection(c);
+        }
+
+        genome
+    }
+
+    #[test]
+    fn test_xor_single_pass() {
+        let mut nn = FeedForward::from_genome(&xor_genome());
+        for (input, want) in [
+            ([0., 0.], 0.),
+            ([1., 0.], 1.),
+            ([0., 1.], 1.),
+            ([1., 1.], 0.),
+        ] {
+            nn.step(1, &input);
+            assert_eq!(nn.output(), [want]);
+        }
+    }
+
+    #[test]
+    fn test_bias() {
+        let mut genome = xor_genome();
+        genome.nodes_mut()[2].bias = -1.;
+        genome.nodes_mut()[4].bias = 0.5;
+        let static_node = genome.static_node();
+        genome.nodes_mut()[static_node].bias = 2.;
+
+        // the static node holds its bias, and the others add theirs before activating
+        let mut nn = FeedForward::from_genome(&genome);
+        for (input, want) in [
+            ([0., 0.], 0.),
+            ([1., 0.], 0.5),
+            ([0., 1.], 0.5),
+            ([1., 1.], 1.5),
+        ] {
+            nn.step(1, &input);
+            assert_eq!(nn.output(), [want]);
+        }
+    }
+
+    #[test]
+    fn test_stateless() {
+        let mut nn = FeedForward::from_genome(&xor_genome());
+        for prec in [1, 2, 10] {
+            nn.step(prec, &[1., 1.]);
+            nn.step(prec, &[1., 0.]);
+            assert_eq!(nn.output(), [1.]);
+        }
+    }
+
+    #[test]
+    fn test_serialization_equivalence() {
+        let mut nn = FeedForward::from_genome(&xor_genome());
+        let mut deserialized = FeedForward::from_str(&nn.to_string().unwrap()).unwrap();
+        for input in [[0., 0.], [1., 0.], [0., 1.], [1., 1.], [0.5, -3.]] {
+            nn.step(1, &input);
+            deserialized.step(1, &input);
+            assert_eq!(nn.output(), deserialized.output());
+        }
+    }
+}
@@ -4,13 +4,19 @@
 //! express their behaviour. The NEAT paper calls for a recurrent network with no per-connection
 //! bias, though maybe we can do more than that here. The code inside is quite experimental.
 
+pub mod binary;
 pub mod continuous;
+pub mod feed_forward;
 pub mod non_bias;
 pub mod simple;
+pub mod sparse;
 
-pub use continuous::Continuous;
+pub use binary::Binary;
+pub use continuous::{Continuous, Integrator};
+pub use feed_forward::FeedForward;
 pub use non_bias::NonBias;
 pub use simple::Simple;
+pub use sparse::{SparseContinuous, SparseNonBias};
 
 use crate::{Connection, Genome};
 use core::error::Error;
@@ -19,6 +25,8 @@ use std::{fs, path::Path};
 
 pub mod activate {
     use core::f64::consts::E;
+    use rand::{seq::IteratorRandom, RngCore};
+    use serde::{Deserialize, Serialize};
 
     pub fn steep_sigmoid(x: f64) -> f64 {
         1. / (1. + E.powf(-4.9 * x))
@@ -31,6 +39,68 @@ pub mod activate {
             x
         }
     }
+
+    pub fn gaussian(x: f64) -> f64 {
+        E.powf(-x * x)
+    }
+
+    pub fn step(x: f64) -> f64 {
+        if x > 0. {
+            1.
+        } else {
+            0.
+        }
+    }
+
+    /// The activation function of a single node, carried by its genome so that it may evolve.
+    #[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+    pub enum Activation {
+        /// [steep_sigmoid], as described by the NEAT paper
+        #[default]
+        Sigmoid,
+        Tanh,
+        Relu,
+        Gaussian,
+        Sin,
+        Abs,
+        Step,
+        Identity,
+    }
+
+    impl Activation {
+        pub const VARIANTS: [Self; 8] = [
+            Self::Sigmoid,
+            Self::Tanh,
+            Self::Relu,
+            Self::Gaussian,
+            Self::Sin,
+            Self::Abs,
+            Self::Step,
+            Self::Identity,
+        ];
+
+        pub fn apply(&self, x: f64) -> f64 {
+            match self {
+                Self::Sigmoid => steep_sigmoid(x),
+                Self::Tanh => x.tanh(),
+                Self::Relu => relu(x),
+                Self::Gaussian => gaussian(x),
+                Self::Sin => x.sin(),
+                Self::Abs => x.abs(),
+                Self::Step => step(x),
+                Self::Identity => x,
+            }
+        }
+
+        /// Some activation other than this one
+        pub fn mutate(&self, rng: &mut impl RngCore) -> Self {
+            Self::VARIANTS
+                .into_iter()
+                .filter(|a| a != self)
+                .choose(rng)
+                .unwrap()
+        }
+    }
 }
 
 pub mod loss {
@@ -49,9 +119,10 @@ pub mod loss {
 
 /// The trait for all networks. Right now, only f64 values are used.
 pub trait Network: Serialize + for<'de> Deserialize<'de> {
-    /// Given some sensory input, step the network with it `prec` times, activating with σ.
-    /// Input must be sized to fit within [Genome::sensory].
-    fn step<F: Fn(f64) -> f64>(&mut self, prec: usize, input: &[f64], σ: F);
+    /// Given some sensory input, step the network with it `prec` times, activating each node
+    /// with its own [Activation](activate::Activation). Input must be sized to fit within
+    /// [Genome::sensory].
+    fn step(&mut self, prec: usize, input: &[f64]);
 
     /// If the network is stateful, flush it's state
     fn flush(&mut self);
@@ -1,5 +1,7 @@
 use super::{FromGenome, Network, Recurrent, Stateful};
 use crate::{
+    activate::Activation,
+    genome::Node,
     serialize::{deserialize_matrix_flat, deserialize_matrix_square, serialize_matrix},
     Connection, Genome,
 };
@@ -13,6 +15,7 @@ pub struct NonBias {
         deserialize_with = "deserialize_matrix_flat"
     )]
     pub y: Matrix<f64>,
+    pub σ: Vec<Activation>,
     #[serde(
         serialize_with = "serialize_matrix",
         deserialize_with = "deserialize_matrix_square"
@@ -23,13 +26,17 @@ pub struct NonBias {
 }
 
 impl Network for NonBias {
-    fn step<F: Fn(f64) -> f64>(&mut self, prec: usize, input: &[f64], σ: F) {
+    fn step(&mut self, prec: usize, input: &[f64]) {
         let mut m_input = Matrix::zeros(1, self.y.cols());
         m_input.mut_data()[self.sensory.0..self.sensory.1].copy_from_slice(input);
 
         let inv = 1. / (prec as f64);
         for _ in 0..prec {
-            self.y = ((&self.y + &m_input).apply(&σ) * &self.w).apply(&|v| v * inv);
+            let mut activated = &self.y + &m_input;
+            for (v, σ) in activated.mut_data().iter_mut().zip(&self.σ) {
+                *v = σ.apply(*v);
+            }
+            self.y = (activated * &self.w).apply(&|v| v * inv);
         }
     }
 
@@ -51,6 +58,7 @@ impl<C: Connection, G: Genome<C>> FromGenome<C, G> for NonBias {
         let cols = genome.nodes().len();
         Self {
             y: Matrix::zeros(1, cols),
+            σ: genome.nodes().iter().map(Node::activation).collect(),
             w: {
                 let mut w = vec![0.; cols * cols];
                 for c in genome.connections().iter().filter(|c| c.enabled()) {
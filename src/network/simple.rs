@@ -1,5 +1,7 @@
 use super::{FromGenome, Network};
-use crate::{genome::NodeKind, serialize::deserialize_connections, Connection, Genome};
+use crate::{
+    activate::Activation, genome::Node, serialize::deserialize_connections, Connection, Genome,
+};
 use core::ops::Range;
 use serde::{Deserialize, Serialize};
 
@@ -10,6 +12,8 @@ pub struct Simple<C: Connection> {
     #[serde(deserialize_with = "deserialize_connections")]
     connections: Vec<C>, // TODO this is copying because of deserialization
     bias: Vec<f64>,
+    /// Activation of each node, applied to connections into it
+    activation: Vec<Activation>,
     #[serde(skip_serializing)]
     state: Vec<f64>,
     #[serde(skip_serializing)]
@@ -19,14 +23,14 @@ pub struct Simple<C: Connection> {
 }
 
 impl<C: Connection> Network for Simple<C> {
-    fn step<F: Fn(f64) -> f64>(&mut self, prec: usize, input: &[f64], σ: F) {
+    fn step(&mut self, prec: usize, input: &[f64]) {
         debug_assert!(input.len() == self.sensory.len());
         self.state[self.sensory.start..self.sensory.end].copy_from_slice(input);
         if !self.connections.is_empty() {
             for _ in 0..prec {
                 for c in self.connections.iter() {
-                    self.state[c.to()] +=
-                        σ((self.bias[c.from()] + self.state[c.from()]) * c.weight())
+                    self.state[c.to()] += self.activation[c.to()]
+                        .apply((self.bias[c.from()] + self.state[c.from()]) * c.weight())
                 }
             }
         }
@@ -45,17 +49,8 @@ impl<C: Connection, G: Genome<C>> FromGenome<C, G> for Simple<C> {
     fn from_genome(genome: &G) -> Self {
         Simple {
             connections: genome.connections().to_owned(),
-            bias: genome
-                .nodes()
-                .iter()
-                .map(|n| {
-                    if matches!(n, NodeKind::Static) {
-                        1.
-                    } else {
-                        0.
-                    }
-                })
-                .collect(),
+            bias: genome.nodes().iter().map(Node::bias).collect(),
+            activation: genome.nodes().iter().map(Node::activation).collect(),
             state: vec![0.; genome.nodes().len()],
             sensory: genome.sensory(),
             action: genome.action(),
@@ -0,0 +1,423 @@
+//! Sparse variants of [Continuous] and [NonBias].
+//!
+//! Evolved genomes tend to have very few connections for how many nodes they have, especially
+//! with many sensory nodes, so most of a dense weight matrix is zeroes. The networks here hold
+//! only the weights who exist, and step in time proportional to the number of connections rather
+//! than the square of the number of nodes. Numerics are the same as their dense counterparts.
+//!
+//! Weights are serialized differently from the dense networks, but a dense network may always be
+//! converted with [From], so a network saved densely may be loaded sparsely with something like
+//! `Continuous::from_file(path).map(SparseContinuous::from)`.
+
+use super::{Continuous, FromGenome, Integrator, NonBias, Recurrent, Stateful};
+use crate::{
+    activate::Activation,
+    genome::Node,
+    serialize::{deserialize_bits, deserialize_matrix_flat, serialize_bits, serialize_matrix},
+    Connection, Genome, Network,
+};
+use rulinalg::matrix::{BaseMatrix, Matrix};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Weights between neurons in compressed sparse column form. Column `to` holds the weights of
+/// every connection into neuron `to`, ordered by the neuron they come from.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SparseWeights {
+    /// Where each column starts in from and weight, followed by where the last one ends
+    offsets: Vec<usize>,
+    /// Source neuron of each weight
+    from: Vec<usize>,
+    #[serde(
+        serialize_with = "serialize_bits",
+        deserialize_with = "deserialize_bits"
+    )]
+    weight: Vec<f64>,
+}
+
+impl SparseWeights {
+    /// Build from (from, to, weight) triples, where later weights between the same neurons
+    /// replace earlier ones. Zero weights are dropped.
+    fn from_triples(size: usize, triples: impl Iterator<Item = (usize, usize, f64)>) -> Self {
+        let mut columns = vec![BTreeMap::new(); size];
+        for (from, to, weight) in triples {
+            columns[to].insert(from, weight);
+        }
+
+        let mut offsets = Vec::with_capacity(size + 1);
+        let mut from = Vec::new();
+        let mut weight = Vec::new();
+        offsets.push(0);
+        for column in columns {
+            for (f, w) in column.into_iter().filter(|(_, w)| *w != 0.) {
+                from.push(f);
+                weight.push(w);
+            }
+            offsets.push(from.len());
+        }
+
+        Self {
+            offsets,
+            from,
+            weight,
+        }
+    }
+
+    /// Weights of every enabled connection in a genome
+    pub fn from_genome<C: Connection, G: Genome<C>>(genome: &G) -> Self {
+        Self::from_triples(
+            genome.nodes().len(),
+            genome
+                .connections()
+                .iter()
+                .filter(|c| c.enabled())
+                .map(|c| (c.from(), c.to(), c.weight())),
+        )
+    }
+
+    /// Non-zero weights of a dense, square matrix indexed as [from, to]
+    pub fn from_dense(w: &Matrix<f64>) -> Self {
+        debug_assert_eq!(w.rows(), w.cols(), "non-square weight matrix");
+        let size = w.cols();
+        Self::from_triples(
+            size,
+            w.data()
+                .iter()
+                .enumerate()
+                .map(|(i, weight)| (i / size, i % size, *weight)),
+        )
+    }
+
+    /// Number of neurons
+    pub fn size(&self) -> usize {
+        self.offsets.len() - 1
+    }
+
+    /// Number of non-zero weights
+    pub fn len(&self) -> usize {
+        self.weight.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.weight.is_empty()
+    }
+
+    /// Weigh state `x` into `out`, such that `out = x * w`
+    pub fn weigh(&self, x: &[f64], out: &mut [f64]) {
+        debug_assert_eq!(x.len(), self.size());
+        debug_assert_eq!(out.len(), self.size());
+        for (to, bound) in self.offsets.windows(2).enumerate() {
+            out[to] = self.from[bound[0]..bound[1]]
+                .iter()
+                .zip(&self.weight[bound[0]..bound[1]])
+                .fold(0., |acc, (from, weight)| acc + x[*from] * weight);
+        }
+    }
+}
+
+/// [Continuous], with sparse weights
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SparseContinuous {
+    /// 1d state of neurons 0-N
+    #[serde(
+        serialize_with = "serialize_matrix",
+        deserialize_with = "deserialize_matrix_flat"
+    )]
+    pub y: Matrix<f64>,
+    /// 1d bias of neurons 0-N
+    #[serde(
+        serialize_with = "serialize_matrix",
+        deserialize_with = "deserialize_matrix_flat"
+    )]
+    pub θ: Matrix<f64>,
+    /// 1d membrane resistance time constant
+    #[serde(
+        serialize_with = "serialize_matrix",
+        deserialize_with = "deserialize_matrix_flat"
+    )]
+    pub τ: Matrix<f64>,
+    /// 1d activation of neurons 0-N
+    pub σ: Vec<Activation>,
+    /// Weights between neurons
+    pub w: SparseWeights,
+    /// Range of input neurons, indexing into y
+    pub sensory: (usize, usize),
+    /// Range of output neurons, indexing into y
+    pub action: (usize, usize),
+    /// How y is integrated through time on each step
+    #[serde(default)]
+    pub integrator: Integrator,
+}
+
+impl Network for SparseContinuous {
+    fn step(&mut self, prec: usize, input: &[f64]) {
+        let mut m_input = vec![0.; self.y.cols()];
+        m_input[self.sensory.0..self.sensory.1].copy_from_slice(input);
+
+        let (θ, τ, σ, w) = (&self.θ, &self.τ, &self.σ, &self.w);
+        let mut activated = vec![0.; self.y.cols()];
+        let mut weighed = vec![0.; self.y.cols()];
+        self.integrator.integrate(self.y.mut_data(), prec, |y, dy| {
+            for (((a, y), θ), σ) in activated.iter_mut().zip(y).zip(θ.data()).zip(σ) {
+                *a = σ.apply(y + θ);
+            }
+            w.weigh(&activated, &mut weighed);
+            for ((((dy, s), y), i), τ) in dy
+                .iter_mut()
+                .zip(&weighed)
+                .zip(y)
+                .zip(&m_input)
+                .zip(τ.data())
+            {
+                *dy = (s - y + i) * τ;
+            }
+        });
+    }
+
+    fn flush(&mut self) {
+        self.y = Matrix::zeros(1, self.y.cols());
+    }
+
+    fn output(&self) -> &[f64] {
+        &self.y.data()[self.action.0..self.action.1]
+    }
+}
+
+impl Recurrent for SparseContinuous {}
+
+impl Stateful for SparseContinuous {}
+
+impl<C: Connection, G: Genome<C>> FromGenome<C, G> for SparseContinuous {
+    fn from_genome(genome: &G) -> Self {
+        let cols = genome.nodes().len();
+        Self {
+            y: Matrix::zeros(1, cols),
+            θ: Matrix::new(
+                1,
+                cols,
+                genome.nodes().iter().map(Node::bias).collect::<Vec<_>>(),
+            ),
+            τ: Matrix::new(
+                1,
+                cols,
+                genome.nodes().iter().map(Node::tau).collect::<Vec<_>>(),
+            ),
+            σ: genome.nodes().iter().map(Node::activation).collect(),
+            w: SparseWeights::from_genome(genome),
+            sensory: (genome.sensory().start, genome.sensory().end),
+            action: (genome.action().start, genome.action().end),
+            integrator: Integrator::default(),
+        }
+    }
+}
+
+impl From<Continuous> for SparseContinuous {
+    fn from(dense: Continuous) -> Self {
+        Self {
+            w: SparseWeights::from_dense(&dense.w),
+            y: dense.y,
+            θ: dense.θ,
+            τ: dense.τ,
+            σ: dense.σ,
+            sensory: dense.sensory,
+            action: dense.action,
+            integrator: dense.integrator,
+        }
+    }
+}
+
+/// [NonBias], with sparse weights
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SparseNonBias {
+    #[serde(
+        serialize_with = "serialize_matrix",
+        deserialize_with = "deserialize_matrix_flat"
+    )]
+    pub y: Matrix<f64>,
+    pub σ: Vec<Activation>,
+    pub w: SparseWeights,
+    pub sensory: (usize, usize),
+    pub action: (usize, usize),
+}
+
+impl Network for SparseNonBias {
+    fn step(&mut self, prec: usize, input: &[f64]) {
+        let mut m_input = vec![0.; self.y.cols()];
+        m_input[self.sensory.0..self.sensory.1].copy_from_slice(input);
+
+        let inv = 1. / (prec as f64);
+        let mut activated = vec![0.; self.y.cols()];
+        for _ in 0..prec {
+            for (((a, y), i), σ) in activated
+                .iter_mut()
+                .zip(self.y.data())
+                .zip(&m_input)
+                .zip(&self.σ)
+            {
+                *a = σ.apply(y + i);
+            }
+            self.w.weigh(&activated, self.y.mut_data());
+            for y in self.y.mut_data() {
+                *y *= inv;
+            }
+        }
+    }
+
+    fn flush(&mut self) {
+        self.y = Matrix::zeros(1, self.y.cols());
+    }
+
+    fn output(&self) -> &[f64] {
+        &self.y.data()[self.action.0..self.action.1]
+    }
+}
+
+impl Recurrent for SparseNonBias {}
+
+impl Stateful for SparseNonBias {}
+
+impl<C: Connection, G: Genome<C>> FromGenome<C, G> for SparseNonBias {
+    fn from_genome(genome: &G) -> Self {
+        Self {
+            y: Matrix::zeros(1, genome.nodes().len()),
+            σ: genome.nodes().iter().map(Node::activation).collect(),
+            w: SparseWeights::from_genome(genome),
+            sensory: (genome.sensory().start, genome.sensory().end),
+            action: (genome.action().start, genome.action().end),
+        }
+    }
+}
+
+impl From<NonBias> for SparseNonBias {
+    fn from(dense: NonBias) -> Self {
+        Self {
+            w: SparseWeights::from_dense(&dense.w),
+            y: dense.y,
+            σ: dense.σ,
+            sensory: dense.sensory,
+            action: dense.action,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        config::MutationConfig,
+        genome::{self, InnoGen, WConnection},
+        random::default_rng,
+    };
+    use rand::Rng;
+
+    type C = WConnection;
+
+    fn sparse_genome() -> genome::Recurrent<C> {
+        let mut rng = default_rng();
+        let (mut genome, inno_head) = genome::Recurrent::<C>::new(20, 4);
+        let mut inno = InnoGen::new(inno_head);
+        for c in genome.connections_mut() {
+            if rng.random_bool(0.8) {
+                c.disable();
+            }
+        }
+        let config = MutationConfig {
+            mutate_connection: 70,
+            mutate_activation: 10,
+            ..MutationConfig::default()
+        };
+        for _ in 0..100 {
+            let _ = genome.mutate(&mut rng, &mut inno, &config);
+        }
+
+        genome
+    }
+
+    #[test]
+    fn test_weights_match_dense() {
+        let genome = sparse_genome();
+        let dense = Continuous::from_genome(&genome);
+        let w = SparseWeights::from_genome(&genome);
+        assert_eq!(w, SparseWeights::from_dense(&dense.w));
+        assert_eq!(w.size(), genome.nodes().len());
+        assert!(w.len() < genome.connections().len());
+    }
+
+    #[test]
+    fn test_continuous_equivalence() {
+        let mut rng = default_rng();
+        let genome = sparse_genome();
+        for integrator in [
+            Integrator::Euler,
+            Integrator::Rk4,
+            Integrator::Rk45 { tolerance: 1e-6 },
+        ] {
+            let mut dense = Continuous::from_genome(&genome);
+            dense.integrator = integrator;
+            let mut sparse = SparseContinuous::from(Continuous::from_genome(&genome));
+            sparse.integrator = integrator;
+            for _ in 0..100 {
+                let input = (0..20)
+                    .map(|_| rng.random_range(-1. ..1.))
+                    .collect::<Vec<_>>();
+                dense.step(5, &input);
+                sparse.step(5, &input);
+                assert_eq!(dense.output(), sparse.output(), "{integrator:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_non_bias_equivalence() {
+        let mut rng = default_rng();
+        let genome = sparse_genome();
+        let mut dense = NonBias::from_genome(&genome);
+        let mut sparse = SparseNonBias::from_genome(&genome);
+        for _ in 0..100 {
+            let input = (0..20)
+                .map(|_| rng.random_range(-1. ..1.))
+                .collect::<Vec<_>>();
+            dense.step(5, &input);
+            sparse.step(5, &input);
+            assert_eq!(dense.output(), sparse.output());
+        }
+    }
+
+    #[test]
+    fn test_from_dense() {
+        let mut rng = default_rng();
+        let genome = sparse_genome();
+        let mut dense = Continuous::from_genome(&genome);
+        let input = vec![0.5; 20];
+        dense.step(3, &input);
+
+        let mut sparse =
+            SparseContinuous::from(Continuous::from_str(&dense.to_string().unwrap()).unwrap());
+        assert_eq!(dense.output(), sparse.output());
+        for _ in 0..10 {
+            let input = (0..20)
+                .map(|_| rng.random_range(-1. ..1.))
+                .collect::<Vec<_>>();
+            dense.step(3, &input);
+            sparse.step(3, &input);
+            assert_eq!(dense.output(), sparse.output());
+        }
+    }
+
+    #[test]
+    fn test_serialization_equivalence() {
+        let mut rng = default_rng();
+        let mut original = SparseContinuous::from_genome(&sparse_genome());
+        original.step(3, &[1.; 20]);
+        let mut deserialized = SparseContinuous::from_str(&original.to_string().unwrap()).unwrap();
+        assert_eq!(original.w, deserialized.w);
+        for _ in 0..10 {
+            let input = (0..20)
+                .map(|_| rng.random_range(-1. ..1.))
+                .collect::<Vec<_>>();
+            original.step(3, &input);
+            deserialized.step(3, &input);
+            assert_eq!(original.output(), deserialized.output());
+        }
+    }
+}
@@ -0,0 +1,127 @@
+//! Novelty search, where genomes are rewarded for behaving unlike any genome seen before rather
+//! than ( or as well as ) for their fitness. Useful for deceptive tasks, whose fitness leads
+//! evolution into dead ends.
+//!
+//! Implementation based on abandoning objectives: evolution through the search for novelty
+//! alone (lehman and stanley 2011).
+
+use crate::config::NoveltyConfig;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+
+/// Euclidean distance between 2 behaviors
+pub fn distance(l: &[f64], r: &[f64]) -> f64 {
+    debug_assert_eq!(l.len(), r.len(), "mismatched behavior length");
+    l.iter()
+        .zip(r)
+        .fold(0., |acc, (l, r)| acc + (l - r).powi(2))
+        .sqrt()
+}
+
+/// Behaviors novel enough to remember, against whom the novelty of every later behavior is
+/// also measured.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Archive {
+    behaviors: VecDeque<Vec<f64>>,
+}
+
+impl Archive {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.behaviors.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.behaviors.is_empty()
+    }
+
+    /// Archived behaviors, oldest first
+    pub fn behaviors(&self) -> impl Iterator<Item = &[f64]> {
+        self.behaviors.iter().map(Vec::as_slice)
+    }
+
+    /// Sparseness of `behaviors[idx]`, the mean distance to its `k` nearest neighbors among
+    /// the rest of `behaviors` and the archive.
+    pub fn sparseness(&self, behaviors: &[Vec<f64>], idx: usize, k: usize) -> f64 {
+        let mut distances = behaviors
+            .iter()
+            .enumerate()
+            .filter(|(other, _)| *other != idx)
+            .map(|(_, b)| b.as_slice())
+            .chain(self.behaviors())
+            .map(|b| distance(&behaviors[idx], b))
+            .collect::<Vec<_>>();
+
+        let k = k.min(distances.len());
+        if k == 0 {
+            return 0.;
+        }
+
+        distances.select_nth_unstable_by(k - 1, f64::total_cmp);
+        distances[..k].iter().sum::<f64>() / k as f64
+    }
+
+    /// Sparseness of every behavior of a generation, in the same order. Those sparser than
+    /// [archive_threshold](NoveltyConfig::archive_threshold) are archived afterwards.
+    pub fn score(&mut self, behaviors: &[Vec<f64>], config: &NoveltyConfig) -> Vec<f64> {
+        let sparseness = (0..behaviors.len())
+            .map(|idx| self.sparseness(behaviors, idx, config.k))
+            .collect::<Vec<_>>();
+
+        for (behavior, s) in behaviors.iter().zip(&sparseness) {
+            if *s > config.archive_threshold {
+                self.behaviors.push_back(behavior.clone());
+            }
+        }
+        while self.behaviors.len() > config.archive_limit {
+            self.behaviors.pop_front();
+        }
+
+        sparseness
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_sparseness() {
+        let behaviors = vec![vec![0., 0.], vec![3., 4.], vec![0., 1.], vec![0., 3.]];
+        let archive = Archive::new();
+        assert_eq!(archive.sparseness(&behaviors, 0, 1), 1.);
+        assert_eq!(archive.sparseness(&behaviors, 0, 2), 2.);
+        assert_eq!(archive.sparseness(&behaviors, 0, 3), 3.);
+        assert_eq!(archive.sparseness(&behaviors, 0, 10), 3.);
+        assert_eq!(archive.sparseness(&behaviors[..1], 0, 10), 0.);
+
+        let archive = Archive {
+            behaviors: VecDeque::from([vec![0., -0.5]]),
+        };
+        assert_eq!(archive.sparseness(&behaviors, 0, 1), 0.5);
+        assert_eq!(archive.sparseness(&behaviors, 0, 2), 0.75);
+    }
+
+    #[test]
+    fn test_score_archives() {
+        let config = NoveltyConfig {
+            k: 1,
+            archive_threshold: 2.,
+            archive_limit: 2,
+            ..NoveltyConfig::default()
+        };
+        let mut archive = Archive::new();
+
+        let sparseness = archive.score(&[vec![0.], vec![1.], vec![10.]], &config);
+        assert_eq!(sparseness, [1., 1., 9.]);
+        assert_eq!(archive.behaviors().collect::<Vec<_>>(), [[10.]]);
+
+        // the archive remembers 10, so 11 is no longer novel
+        let sparseness = archive.score(&[vec![11.], vec![-5.], vec![20.]], &config);
+        assert_eq!(sparseness, [1., 15., 9.]);
+        assert_eq!(archive.behaviors().collect::<Vec<_>>(), [[-5.], [20.]]);
+    }
+}
@@ -0,0 +1,171 @@
+//! Functions for ranking genomes by several objectives at once, rather than a single fitness.
+//!
+//! Ranking is done by non-dominated sorting and crowding distance, as described by a fast and
+//! elitist multiobjective genetic algorithm: NSGA-II (deb 2002). Like fitness, every objective
+//! is maximized.
+
+use core::f64;
+
+/// Whether `l` is at least as good as `r` in every objective, and better in at least one
+pub fn dominates(l: &[f64], r: &[f64]) -> bool {
+    debug_assert_eq!(l.len(), r.len(), "mismatched objective count");
+    l.iter().zip(r).all(|(l, r)| l >= r) && l.iter().zip(r).any(|(l, r)| l > r)
+}
+
+/// Partition `objectives` into pareto fronts of their indices. The first front is dominated by
+/// nobody, the second only by members of the first, and so on.
+pub fn fronts(objectives: &[Vec<f64>]) -> Vec<Vec<usize>> {
+    let mut dominated = vec![Vec::new(); objectives.len()];
+    let mut count = vec![0; objectives.len()];
+    for (l_idx, l) in objectives.iter().enumerate() {
+        for (r_idx, r) in objectives.iter().enumerate().skip(l_idx + 1) {
+            if dominates(l, r) {
+                dominated[l_idx].push(r_idx);
+                count[r_idx] += 1;
+            } else if dominates(r, l) {
+                dominated[r_idx].push(l_idx);
+                count[l_idx] += 1;
+            }
+        }
+    }
+
+    let mut fronts = Vec::new();
+    let mut front = (0..objectives.len())
+        .filter(|idx| count[*idx] == 0)
+        .collect::<Vec<_>>();
+    while !front.is_empty() {
+        let mut next = Vec::new();
+        for idx in front.iter() {
+            for d in dominated[*idx].iter() {
+                count[*d] -= 1;
+                if count[*d] == 0 {
+                    next.push(*d);
+                }
+            }
+        }
+
+        fronts.push(front);
+        front = next;
+    }
+
+    fronts
+}
+
+/// Crowding distance of each member of `front`, in the same order. Members at the extremes of
+/// any objective are infinitely far from the crowd, and the rest are as far as the normalized
+/// distance between their neighbors in each objective, summed.
+pub fn crowding(objectives: &[Vec<f64>], front: &[usize]) -> Vec<f64> {
+    let mut distance = vec![0.; front.len()];
+    let Some(count) = front.first().map(|idx| objectives[*idx].len()) else {
+        return distance;
+    };
+
+    let mut order = (0..front.len()).collect::<Vec<_>>();
+    for column in (0..count).map(|o| {
+        front
+            .iter()
+            .map(|idx| objectives[*idx][o])
+            .collect::<Vec<_>>()
+    }) {
+        order.sort_by(|l, r| column[*l].total_cmp(&column[*r]));
+
+        let (first, last) = (order[0], order[order.len() - 1]);
+        distance[first] = f64::INFINITY;
+        distance[last] = f64::INFINITY;
+
+        let span = column[last] - column[first];
+        if span > 0. {
+            for window in order.windows(3) {
+                distance[window[1]] += (column[window[2]] - column[window[0]]) / span;
+            }
+        }
+    }
+
+    distance
+}
+
+/// A single fitness for each of `objectives`, such that any member of an earlier front is
+/// fitter than every member of a later one, and within a front the less crowded are fitter.
+/// Members of front `k` have a fitness within `(1 / (k + 2), 1 / (k + 1)]`.
+pub fn rank(objectives: &[Vec<f64>]) -> Vec<f64> {
+    let mut fitness = vec![0.; objectives.len()];
+    for (k, front) in fronts(objectives).into_iter().enumerate() {
+        let (hi, lo) = (1. / (k + 1) as f64, 1. / (k + 2) as f64);
+        for (idx, distance) in front.iter().zip(crowding(objectives, &front)) {
+            let spread = if distance.is_infinite() {
+                1.
+            } else {
+                (1. + distance) / (2. + distance)
+            };
+            fitness[*idx] = lo + (hi - lo) * spread;
+        }
+    }
+
+    fitness
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_dominates() {
+        assert!(dominates(&[1., 1.], &[0., 1.]));
+        assert!(!dominates(&[0., 1.], &[1., 1.]));
+        assert!(!dominates(&[1., 1.], &[1., 1.]));
+        assert!(!dominates(&[1., 0.], &[0., 1.]));
+        assert!(!dominates(&[0., 1.], &[1., 0.]));
+    }
+
+    #[test]
+    fn test_fronts() {
+        let objectives = vec![
+            vec![3., 0.],
+            vec![2., 2.],
+            vec![0., 3.],
+            vec![1., 1.],
+            vec![2., 0.],
+            vec![0., 0.],
+        ];
+        let mut fronts = fronts(&objectives);
+        for front in fronts.iter_mut() {
+            front.sort();
+        }
+        assert_eq!(fronts, vec![vec![0, 1, 2], vec![3, 4], vec![5]]);
+    }
+
+    #[test]
+    fn test_crowding() {
+        let objectives = vec![vec![0., 4.], vec![1., 3.], vec![3., 1.], vec![4., 0.]];
+        let distance = crowding(&objectives, &[0, 1, 2, 3]);
+        assert!(distance[0].is_infinite());
+        assert!(distance[3].is_infinite());
+        assert_eq!(distance[1], 3. / 4. + 3. / 4.);
+        assert_eq!(distance[1], distance[2]);
+
+        assert!(crowding(&objectives, &[]).is_empty());
+        assert!(crowding(&objectives, &[2])[0].is_infinite());
+    }
+
+    #[test]
+    fn test_rank() {
+        let objectives = vec![
+            vec![0., 4.],
+            vec![1., 3.],
+            vec![2., 2.5],
+            vec![4., 0.],
+            vec![1., 1.],
+            vec![0., 0.],
+        ];
+        let fitness = rank(&objectives);
+        assert_eq!(fitness[0], 1.);
+        assert_eq!(fitness[3], 1.);
+        for front_0 in 0..4 {
+            assert!(fitness[front_0] > 0.5);
+            assert!(fitness[front_0] > fitness[4]);
+        }
+        assert!(fitness[2] > fitness[1]);
+        assert!(fitness[4] > fitness[5]);
+        assert!(fitness[5] > 0.);
+    }
+}
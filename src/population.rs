@@ -1,20 +1,29 @@
 //! Functions and structs related to managing genomes at the specie and global population scale.
 
 use crate::{
+    activate::Activation,
+    config::{EvolutionConfig, SpeciationStrategy},
     crossover::delta,
-    genome::{Connection, Genome},
+    genome::{Connection, Genome, Node},
+    serialize::deserialize_connections,
 };
 use core::{
     error::Error,
     f64,
     hash::{Hash, Hasher},
 };
+use rand::{seq::index::sample, Rng, RngCore};
+#[cfg(feature = "parallel")]
+use rayon::iter::{IntoParallelRefIterator, ParallelIterator};
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "parallel")]
+use std::iter::once;
 use std::{fs::read_dir, hash::DefaultHasher, iter::empty, path::Path};
 
 /// The representative member of a particular specie. Is retained inter-generationally to better
 /// track when a specie deviates
-#[derive(Debug, Clone)]
-pub struct SpecieRepr<C: Connection>(Vec<C>);
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SpecieRepr<C: Connection>(#[serde(deserialize_with = "deserialize_connections")] Vec<C>);
 
 impl<C: Connection> SpecieRepr<C> {
     pub fn new(v: Vec<C>) -> Self {
@@ -66,10 +75,7 @@ pub trait FittedGroup<T> {
 
 impl<T> FittedGroup<T> for [(T, f64)] {
     fn fittest(&self) -> Option<&(T, f64)> {
-        self.iter().max_by(|(_, l), (_, r)| {
-            l.partial_cmp(r)
-                .unwrap_or_else(|| panic!("could not compare specie member fitness {l} to {r}"))
-        })
+        self.iter().max_by(|(_, l), (_, r)| l.total_cmp(r))
     }
 
     fn fit_adjusted(&self) -> f64 {
@@ -78,14 +84,76 @@ impl<T> FittedGroup<T> for [(T, f64)] {
     }
 }
 
-/// A collection of fitted [Genome]s who are closely related to the same [SpecieRepr]
-#[derive(Debug, Clone)]
+/// A collection of fitted [Genome]s who are closely related to the same [SpecieRepr]. A specie
+/// keeps its identity across generations for as long as somebody still matches its repr.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(bound(deserialize = ""))]
 pub struct Specie<C: Connection, G: Genome<C>> {
     pub repr: SpecieRepr<C>,
     pub members: Vec<(G, f64)>,
+    /// Unique among every specie of a population, and never reused
+    #[serde(default)]
+    pub id: usize,
+    /// Generation this specie was formed in
+    #[serde(default)]
+    pub born: usize,
+    /// Generation this specie last improved on its best fitness in
+    #[serde(default)]
+    pub improved: usize,
+    /// Best fitness that any member has ever had
+    #[serde(default = "fitness_min")]
+    pub best: f64,
+}
+
+fn fitness_min() -> f64 {
+    f64::MIN
 }
 
 impl<C: Connection, G: Genome<C>> Specie<C, G> {
+    /// A new specie without any members, formed in `generation`
+    pub fn new(repr: SpecieRepr<C>, id: usize, generation: usize) -> Self {
+        Self {
+            repr,
+            members: Vec::new(),
+            id,
+            born: generation,
+            improved: generation,
+            best: f64::MIN,
+        }
+    }
+
+    /// Generations since this specie was formed
+    #[inline]
+    pub fn age(&self, generation: usize) -> usize {
+        generation.saturating_sub(self.born)
+    }
+
+    /// Generations since this specie last improved
+    #[inline]
+    pub fn stagnant(&self, generation: usize) -> usize {
+        generation.saturating_sub(self.improved)
+    }
+
+    /// Record `fitness` as achieved in `generation`, if it's better than our best
+    pub fn improve(&mut self, fitness: f64, generation: usize) {
+        if fitness > self.best {
+            self.best = fitness;
+            self.improved = generation;
+        }
+    }
+
+    /// This specie's identity and history, without any of its members
+    pub fn lineage(&self) -> Self {
+        Self {
+            repr: self.repr.clone(),
+            members: Vec::new(),
+            id: self.id,
+            born: self.born,
+            improved: self.improved,
+            best: self.best,
+        }
+    }
+
     #[inline]
     pub fn len(&self) -> usize {
         self.members.len()
@@ -120,36 +188,347 @@ impl<C: Connection, G: Genome<C>> FittedGroup<G> for Specie<C, G> {
     }
 }
 
-const SPECIE_THRESHOLD: f64 = 4.;
-
-/// Partition an unordered collection of [Genome]s into species. An initial collection of empty
-/// species is created from repr, and if some genome matches none of them, a new specie is
-/// formed with them as the repr.
+/// Partition an unordered collection of [Genome]s into species. The species of some past
+/// generation are carried on without their members, and if some genome matches none of them,
+/// a new specie is formed with them as the repr. A genome matches some repr when their delta is
+/// below `threshold`. New species are formed in `generation`, and take their ids from
+/// `specie_head`.
 pub fn speciate<C: Connection, G: Genome<C>>(
     genomes: impl Iterator<Item = (G, f64)>,
-    reprs: impl Iterator<Item = SpecieRepr<C>>,
+    lineage: impl Iterator<Item = Specie<C, G>>,
+    threshold: f64,
+    specie_head: &mut usize,
+    generation: usize,
 ) -> Vec<Specie<C, G>> {
-    let mut sp = Vec::from_iter(reprs.map(|repr| Specie {
-        repr,
-        members: Vec::new(),
+    let mut sp = Vec::from_iter(lineage.map(|mut specie| {
+        specie.members.clear();
+        specie
     }));
 
+    speciate_into(&mut sp, genomes, threshold, specie_head, generation);
+    sp
+}
+
+/// Like [speciate], but adding to some existing species, whose members are left alone
+pub fn speciate_into<C: Connection, G: Genome<C>>(
+    species: &mut Vec<Specie<C, G>>,
+    genomes: impl Iterator<Item = (G, f64)>,
+    threshold: f64,
+    specie_head: &mut usize,
+    generation: usize,
+) {
     for (genome, fitness) in genomes {
-        match sp
+        match species
             .iter_mut()
-            .find(|Specie { repr, .. }| repr.delta(genome.connections()) < SPECIE_THRESHOLD)
+            .find(|Specie { repr, .. }| repr.delta(genome.connections()) < threshold)
         {
             Some(Specie { members, .. }) => members.push((genome, fitness)),
             None => {
-                sp.push(Specie {
-                    repr: SpecieRepr::new(genome.connections().to_vec()),
-                    members: vec![(genome, fitness)],
+                let mut specie = Specie::new(
+                    SpecieRepr::new(genome.connections().to_vec()),
+                    *specie_head,
+                    generation,
+                );
+                specie.members.push((genome, fitness));
+                species.push(specie);
+                *specie_head += 1;
+            }
+        }
+    }
+}
+
+/// A strategy for partitioning genomes into species
+pub trait Speciation<C: Connection, G: Genome<C>> {
+    /// Partition `genomes` into species, carrying on the identities of `lineage` ( the species
+    /// of some past generation ). Like [speciate], new species are formed in `generation` and
+    /// take their ids from `specie_head`.
+    fn speciate(
+        &self,
+        genomes: Vec<(G, f64)>,
+        lineage: Vec<Specie<C, G>>,
+        threshold: f64,
+        specie_head: &mut usize,
+        generation: usize,
+        rng: &mut impl RngCore,
+    ) -> Vec<Specie<C, G>>;
+}
+
+/// Speciation by [speciate], where each genome joins the first specie whose repr it matches.
+/// Reprs are the genomes who founded their specie.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct FirstMatch;
+
+impl<C: Connection, G: Genome<C>> Speciation<C, G> for FirstMatch {
+    fn speciate(
+        &self,
+        genomes: Vec<(G, f64)>,
+        lineage: Vec<Specie<C, G>>,
+        threshold: f64,
+        specie_head: &mut usize,
+        generation: usize,
+        _: &mut impl RngCore,
+    ) -> Vec<Specie<C, G>> {
+        speciate(
+            genomes.into_iter(),
+            lineage.into_iter(),
+            threshold,
+            specie_head,
+            generation,
+        )
+    }
+}
+
+/// Like [FirstMatch], but afterwards a random member of each specie becomes its repr, as in
+/// the original NEAT. Species drift along with their members, rather than being anchored to
+/// their founder.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RandomRepr;
+
+impl<C: Connection, G: Genome<C>> Speciation<C, G> for RandomRepr {
+    fn speciate(
+        &self,
+        genomes: Vec<(G, f64)>,
+        lineage: Vec<Specie<C, G>>,
+        threshold: f64,
+        specie_head: &mut usize,
+        generation: usize,
+        rng: &mut impl RngCore,
+    ) -> Vec<Specie<C, G>> {
+        let mut species =
+            FirstMatch.speciate(genomes, lineage, threshold, specie_head, generation, rng);
+        for specie in species.iter_mut().filter(|specie| !specie.is_empty()) {
+            let (repr, _) = &specie.members[rng.random_range(0..specie.len())];
+            specie.repr = SpecieRepr::new(repr.connections().to_vec());
+        }
+
+        species
+    }
+}
+
+/// Speciation by k-medoids clustering on the delta between every pair of genomes, forming
+/// `k` species ( or fewer, if there are fewer genomes ) whose medoids become their reprs.
+/// Clusters are seeded by the genomes nearest the reprs of `lineage`, and then at random, and
+/// refined for at most `iterations`. A cluster takes on the identity of the first specie of
+/// `lineage` whose repr its medoid matches, and forms a new specie otherwise.
+#[derive(Debug, Clone, Copy)]
+pub struct KMedoids {
+    pub k: usize,
+    pub iterations: usize,
+}
+
+impl KMedoids {
+    /// Index of the medoid nearest to each genome
+    fn assign(distance: &[Vec<f64>], medoids: &[usize]) -> Vec<usize> {
+        (0..distance.len())
+            .map(|idx| {
+                (0..medoids.len())
+                    .min_by(|l, r| {
+                        distance[medoids[*l]][idx].total_cmp(&distance[medoids[*r]][idx])
+                    })
+                    .unwrap()
+            })
+            .collect()
+    }
+}
+
+impl<C: Connection, G: Genome<C>> Speciation<C, G> for KMedoids {
+    fn speciate(
+        &self,
+        genomes: Vec<(G, f64)>,
+        lineage: Vec<Specie<C, G>>,
+        threshold: f64,
+        specie_head: &mut usize,
+        generation: usize,
+        rng: &mut impl RngCore,
+    ) -> Vec<Specie<C, G>> {
+        let mut lineage = lineage
+            .into_iter()
+            .map(|mut specie| {
+                specie.members.clear();
+                specie
+            })
+            .collect::<Vec<_>>();
+        let k = self.k.min(genomes.len());
+        if k == 0 {
+            return lineage;
+        }
+
+        // delta isn't symmetric, so distance is measured from whoever is ( or may become ) the
+        // medoid
+        let distance = genomes
+            .iter()
+            .map(|(l, _)| {
+                genomes
+                    .iter()
+                    .map(|(r, _)| delta(l.connections(), r.connections()).abs())
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+
+        let mut medoids = Vec::with_capacity(k);
+        for Specie { repr, .. } in lineage.iter().take(k) {
+            let nearest = (0..genomes.len())
+                .filter(|idx| !medoids.contains(idx))
+                .min_by(|l, r| {
+                    repr.delta(genomes[*l].0.connections())
+                        .abs()
+                        .total_cmp(&repr.delta(genomes[*r].0.connections()).abs())
                 });
+            medoids.extend(nearest);
+        }
+        let rest = (0..genomes.len())
+            .filter(|idx| !medoids.contains(idx))
+            .collect::<Vec<_>>();
+        medoids.extend(
+            sample(rng, rest.len(), k - medoids.len())
+                .into_iter()
+                .map(|idx| rest[idx]),
+        );
+
+        let mut clusters = Self::assign(&distance, &medoids);
+        for _ in 0..self.iterations {
+            let next = (0..k)
+                .map(|cluster| {
+                    let members = (0..genomes.len())
+                        .filter(|idx| clusters[*idx] == cluster)
+                        .collect::<Vec<_>>();
+                    members
+                        .iter()
+                        .copied()
+                        .min_by(|l, r| {
+                            let cost =
+                                |m: usize| members.iter().map(|idx| distance[m][*idx]).sum::<f64>();
+                            cost(*l).total_cmp(&cost(*r))
+                        })
+                        .unwrap_or(medoids[cluster])
+                })
+                .collect::<Vec<_>>();
+
+            if next == medoids {
+                break;
+            }
+            medoids = next;
+            clusters = Self::assign(&distance, &medoids);
+        }
+
+        // clusters take on the identity of the first unclaimed specie whose repr they match
+        let mut species = vec![None; k];
+        let mut fresh = Vec::new();
+        for (cluster, medoid) in medoids.iter().enumerate() {
+            let connections = genomes[*medoid].0.connections();
+            let repr = SpecieRepr::new(connections.to_vec());
+            match lineage.iter().position(|specie| {
+                !species.contains(&Some(specie.id))
+                    && specie.repr.delta(connections).abs() < threshold
+            }) {
+                Some(idx) => {
+                    species[cluster] = Some(lineage[idx].id);
+                    lineage[idx].repr = repr;
+                }
+                None => {
+                    species[cluster] = Some(*specie_head);
+                    fresh.push(Specie::new(repr, *specie_head, generation));
+                    *specie_head += 1;
+                }
             }
         }
+
+        lineage.extend(fresh);
+        for ((genome, fitness), cluster) in genomes.into_iter().zip(clusters) {
+            let id = species[cluster].unwrap();
+            lineage
+                .iter_mut()
+                .find(|specie| specie.id == id)
+                .unwrap()
+                .members
+                .push((genome, fitness));
+        }
+
+        lineage
     }
+}
 
-    sp
+/// Speciates exactly like [FirstMatch], but matches genomes against the species of `lineage`
+/// in parallel, in whichever thread-pool it's called from. Only those who match none of them
+/// are speciated sequentially.
+#[cfg(feature = "parallel")]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ParallelFirstMatch;
+
+#[cfg(feature = "parallel")]
+impl<C: Connection + Sync, G: Genome<C> + Send + Sync> Speciation<C, G> for ParallelFirstMatch {
+    fn speciate(
+        &self,
+        genomes: Vec<(G, f64)>,
+        lineage: Vec<Specie<C, G>>,
+        threshold: f64,
+        specie_head: &mut usize,
+        generation: usize,
+        _: &mut impl RngCore,
+    ) -> Vec<Specie<C, G>> {
+        let mut species = speciate(
+            empty(),
+            lineage.into_iter(),
+            threshold,
+            specie_head,
+            generation,
+        );
+
+        let matches = genomes
+            .par_iter()
+            .map(|(genome, _)| {
+                species
+                    .iter()
+                    .position(|Specie { repr, .. }| repr.delta(genome.connections()) < threshold)
+            })
+            .collect::<Vec<_>>();
+        for (member, matched) in genomes.into_iter().zip(matches) {
+            match matched {
+                Some(idx) => species[idx].members.push(member),
+                None => speciate_into(
+                    &mut species,
+                    once(member),
+                    threshold,
+                    specie_head,
+                    generation,
+                ),
+            }
+        }
+
+        species
+    }
+}
+
+impl<C: Connection, G: Genome<C>> Speciation<C, G> for SpeciationStrategy {
+    /// Speciate by whichever strategy this is. [Parallel](SpeciationStrategy::Parallel)
+    /// speciation gives the same species as [FirstMatch], and so is done sequentially unless
+    /// the genomes can be shared between threads.
+    fn speciate(
+        &self,
+        genomes: Vec<(G, f64)>,
+        lineage: Vec<Specie<C, G>>,
+        threshold: f64,
+        specie_head: &mut usize,
+        generation: usize,
+        rng: &mut impl RngCore,
+    ) -> Vec<Specie<C, G>> {
+        match *self {
+            Self::FirstMatch | Self::Parallel => {
+                FirstMatch.speciate(genomes, lineage, threshold, specie_head, generation, rng)
+            }
+            Self::RandomRepr => {
+                RandomRepr.speciate(genomes, lineage, threshold, specie_head, generation, rng)
+            }
+            Self::KMedoids { k, iterations } => KMedoids { k, iterations }.speciate(
+                genomes,
+                lineage,
+                threshold,
+                specie_head,
+                generation,
+                rng,
+            ),
+        }
+    }
 }
 
 pub type SpecieGroup<C, G> = (Vec<Specie<C, G>>, usize);
@@ -162,11 +541,25 @@ pub fn population_init<C: Connection, G: Genome<C>>(
     action: usize,
     population: usize,
 ) -> SpecieGroup<C, G> {
-    let (genome, inno_head) = G::new(sensory, action);
+    population_init_with(sensory, action, population, Activation::default())
+}
+
+/// [population_init], but with every node of the initial genome taking `activation`. Pair it
+/// with the same [activation](crate::config::MutationConfig::activation) for nodes grown later.
+pub fn population_init_with<C: Connection, G: Genome<C>>(
+    sensory: usize,
+    action: usize,
+    population: usize,
+    activation: Activation,
+) -> SpecieGroup<C, G> {
+    let (mut genome, inno_head) = G::new(sensory, action);
+    for node in genome.nodes_mut() {
+        node.set_activation(activation);
+    }
     (
         vec![Specie {
-            repr: SpecieRepr::new(genome.connections().to_vec()),
-            members: vec![(genome, f64::MIN); population],
+            members: vec![(genome.clone(), f64::MIN); population],
+            ..Specie::new(SpecieRepr::new(genome.connections().to_vec()), 0, 0)
         }],
         inno_head,
     )
@@ -207,7 +600,16 @@ pub fn population_from_files<P: AsRef<Path>, C: Connection, G: Genome<C>>(
         .max()
         .unwrap_or(0);
 
-    Ok((speciate(pop_flat.into_iter(), empty()), inno_head))
+    Ok((
+        speciate(
+            pop_flat.into_iter(),
+            empty(),
+            EvolutionConfig::default().specie_threshold,
+            &mut 0,
+            0,
+        ),
+        inno_head,
+    ))
 }
 
 /// Load a single [Genome] from a single file, and clone it `population` times. Useful for
@@ -225,7 +627,13 @@ pub fn population_from_genome<P: AsRef<Path>, C: Connection, G: Genome<C>>(
         .unwrap_or(0);
 
     Ok((
-        speciate(vec![(muse, f64::MIN); population].into_iter(), empty()),
+        speciate(
+            vec![(muse, f64::MIN); population].into_iter(),
+            empty(),
+            EvolutionConfig::default().specie_threshold,
+            &mut 0,
+            0,
+        ),
         inno_head,
     ))
 }
@@ -234,7 +642,10 @@ pub fn population_from_genome<P: AsRef<Path>, C: Connection, G: Genome<C>>(
 mod test {
     use super::*;
     use crate::{
-        genome::{Recurrent, WConnection},
+        config::MutationConfig,
+        genome::{InnoGen, Recurrent, WConnection},
+        macros::fixtures::weighted,
+        random::WyRng,
         test_t,
     };
 
@@ -263,4 +674,152 @@ mod test {
             assert_eq!(f64::MIN, *fit);
         }
     });
+
+    test_t!(test_population_init_with[T: BasicGenomeCtrnn]() {
+        let (species, inno_head) =
+            population_init_with::<WConnection, T>(2, 2, 4, Activation::Relu);
+        let (mut genome, _) = species[0].members[0].clone();
+        assert!(genome.nodes().iter().all(|n| n.activation() == Activation::Relu));
+
+        // nodes grown later take the activation of the mutation config
+        let config = MutationConfig {
+            new_connection: 0,
+            bisect_connection: 100,
+            mutate_connection: 0,
+            activation: Activation::Tanh,
+            ..MutationConfig::default()
+        };
+        let mut innogen = InnoGen::new(inno_head);
+        genome
+            .mutate(&mut WyRng::seeded(0), &mut innogen, &config)
+            .unwrap();
+        assert_eq!(genome.nodes().len(), 6);
+        assert_eq!(genome.nodes()[5].activation(), Activation::Tanh);
+        assert!(genome.nodes()[..5]
+            .iter()
+            .all(|n| n.activation() == Activation::Relu));
+    });
+
+    test_t!(test_speciate_lineage[T: BasicGenomeCtrnn]() {
+        let mut head = 0;
+        let species = speciate(weighted::<T>([0., -100., 0.]).into_iter(), empty(), 1., &mut head, 3);
+        assert_eq!(head, 2);
+        assert_eq!(
+            species
+                .iter()
+                .map(|s| (s.id, s.born, s.len()))
+                .collect::<Vec<_>>(),
+            [(0, 3, 2), (1, 3, 1)]
+        );
+
+        // species carry on without their members, and newcomers take the next id
+        let mut lineage = species;
+        lineage[1].improve(-100., 4);
+        let species = speciate(
+            weighted::<T>([-200., -100.]).into_iter(),
+            lineage.into_iter(),
+            1.,
+            &mut head,
+            5,
+        );
+        assert_eq!(
+            species
+                .iter()
+                .map(|s| (s.id, s.born, s.improved, s.len()))
+                .collect::<Vec<_>>(),
+            [(0, 3, 3, 0), (1, 3, 4, 1), (2, 5, 5, 1)]
+        );
+        assert_eq!(species[1].best, -100.);
+        assert_eq!(species[1].stagnant(6), 2);
+        assert_eq!(species[1].age(6), 3);
+    });
+
+    fn weights<T: Genome<WConnection>>(specie: &Specie<WConnection, T>) -> Vec<f64> {
+        specie.members.iter().map(|(_, weight)| *weight).collect()
+    }
+
+    test_t!(test_random_repr[T: BasicGenomeCtrnn]() {
+        let mut rng = WyRng::seeded(0);
+        let species = RandomRepr.speciate(
+            weighted::<T>([0., 0.5, 1., -100., -100.5]),
+            Vec::new(),
+            5.,
+            &mut 0,
+            0,
+            &mut rng,
+        );
+        assert_eq!(species.len(), 2);
+        for specie in species {
+            assert!(specie
+                .members
+                .iter()
+                .any(|(genome, _)| genome.connections() == specie.repr.as_ref()));
+        }
+    });
+
+    test_t!(test_k_medoids[T: BasicGenomeCtrnn]() {
+        let strategy = KMedoids {
+            k: 3,
+            iterations: 10,
+        };
+        let mut rng = WyRng::seeded(0);
+        let mut head = 0;
+        let groups = [0., 1., 2., 50., 51., 52., 100., 101., 102.];
+        let species = strategy.speciate(weighted::<T>(groups), Vec::new(), 1., &mut head, 0, &mut rng);
+        assert_eq!(head, 3);
+
+        let mut clusters = species.iter().map(weights).collect::<Vec<_>>();
+        clusters.sort_by(|l, r| l[0].total_cmp(&r[0]));
+        assert_eq!(clusters, [[0., 1., 2.], [50., 51., 52.], [100., 101., 102.]]);
+        for specie in species.iter() {
+            let (medoid, _) = specie
+                .members
+                .iter()
+                .find(|(genome, _)| genome.connections() == specie.repr.as_ref())
+                .unwrap();
+            assert_eq!(medoid.connections()[0].weight, weights(specie)[1]);
+        }
+
+        // clusters near the last generation's medoids carry on their identity
+        let ids = species
+            .iter()
+            .map(|specie| (weights(specie)[1], specie.id))
+            .collect::<Vec<_>>();
+        let species = strategy.speciate(
+            weighted::<T>([100.5, 50.5, 0.5, 1.5]),
+            species,
+            1.,
+            &mut head,
+            1,
+            &mut rng,
+        );
+        assert_eq!(head, 3);
+        for specie in species {
+            let (_, id) = ids
+                .iter()
+                .find(|(medoid, _)| (weights(&specie)[0] - medoid).abs() < 1.)
+                .unwrap();
+            assert_eq!(specie.id, *id);
+        }
+
+        assert!(strategy
+            .speciate(Vec::<(T, f64)>::new(), Vec::new(), 1., &mut head, 2, &mut rng)
+            .is_empty());
+    });
+
+    #[cfg(feature = "parallel")]
+    test_t!(test_parallel_first_match[T: BasicGenomeCtrnn]() {
+        let mut rng = WyRng::seeded(0);
+        let lineage = speciate(weighted::<T>([0., -50.]).into_iter(), empty(), 5., &mut 0, 0);
+        let genomes = weighted::<T>([1., -200., -49., 3., -201., 60., -2.]);
+
+        let (mut head_l, mut head_r) = (2, 2);
+        let sequential = FirstMatch.speciate(genomes.clone(), lineage.clone(), 5., &mut head_l, 1, &mut rng);
+        let parallel = ParallelFirstMatch.speciate(genomes, lineage, 5., &mut head_r, 1, &mut rng);
+        assert_eq!(head_l, head_r);
+        assert_eq!(
+            sequential.iter().map(|s| (s.id, weights(s))).collect::<Vec<_>>(),
+            parallel.iter().map(|s| (s.id, weights(s))).collect::<Vec<_>>()
+        );
+    });
 }
@@ -3,6 +3,7 @@
 use core::cmp::min;
 use find_fold::FindFold;
 use rand::RngCore;
+use serde::{Deserialize, Serialize};
 use std::{
     fs::File,
     io::{self, Read},
@@ -28,9 +29,12 @@ use crate::events;
 ///
 /// // This will pass about 10% of the time!
 /// default_rng().next_u64() < percent(10);
+///
+/// // and nothing is more certain than certain
+/// assert_eq!(percent(150), percent(100));
 /// ```
 pub const fn percent(x: u64) -> u64 {
-    x * (u64::MAX / 100)
+    (if x > 100 { 100 } else { x }) * (u64::MAX / 100)
 }
 
 /// A quick and dirty way to get an RNG seed from urandom, onsystems that support it. Useful
@@ -51,6 +55,7 @@ pub fn default_rng() -> impl RngCore {
 }
 
 /// A really small but also fast random number generator. Lifted from smol-rs/fastrand
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct WyRng {
     state: u64,
 }
@@ -114,5 +119,5 @@ pub trait EventKind: Copy {
     }
 }
 
-events!(Genome[NewConnection, BisectConnection, MutateConnection, MutateNode]);
+events!(Genome[NewConnection, BisectConnection, MutateConnection, MutateNode, MutateActivation]);
 events!(Connection[Disable, MutateParam]);
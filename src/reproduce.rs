@@ -1,64 +1,279 @@
 //! Functions related to reproducing on the specie and global population scale.
 
 use crate::{
+    config::{EvolutionConfig, SelectionStrategy},
+    error::EvolutionError,
     genome::{Connection, Genome, InnoGen},
     population::FittedGroup,
     Specie,
 };
-use core::{error::Error, f64};
-use rand::RngCore;
+use core::f64;
+use rand::{Rng, RngCore};
 
-fn reproduce_crossover<C: Connection, G: Genome<C>>(
-    genomes: &[(G, f64)],
-    size: usize,
-    rng: &mut impl RngCore,
-    innogen: &mut InnoGen,
-) -> Result<Vec<G>, Box<dyn Error>> {
-    if size == 0 {
-        return Ok(vec![]);
+/// A scheme for picking the parents of each child from the fitted members of a specie
+pub trait Selection {
+    /// Index of a single parent picked from `genomes`, which is never empty
+    fn select<G>(&self, genomes: &[(G, f64)], rng: &mut impl RngCore) -> usize;
+
+    /// Indices of `count` parents to be copied
+    fn parents<G>(&self, genomes: &[(G, f64)], count: usize, rng: &mut impl RngCore) -> Vec<usize> {
+        (0..count).map(|_| self.select(genomes, rng)).collect()
     }
 
-    if genomes.len() < 2 {
-        return Err(format!(
-            "too few members to crossover (wanted to produce {size} from {}",
-            genomes.len()
-        )
-        .into());
+    /// Indices of `count` pairs of distinct parents to be crossed over, the fitter of each pair
+    /// first. `genomes` holds at least 2 members.
+    fn pairs<G>(
+        &self,
+        genomes: &[(G, f64)],
+        count: usize,
+        rng: &mut impl RngCore,
+    ) -> Vec<(usize, usize)> {
+        (0..count)
+            .map(|_| {
+                let l = self.select(genomes, rng);
+                let r = (0..PAIR_ATTEMPTS)
+                    .map(|_| self.select(genomes, rng))
+                    .find(|r| *r != l)
+                    .unwrap_or_else(|| {
+                        (l + 1 + rng.random_range(0..genomes.len() - 1)) % genomes.len()
+                    });
+
+                if genomes[l].1.total_cmp(&genomes[r].1).is_ge() {
+                    (l, r)
+                } else {
+                    (r, l)
+                }
+            })
+            .collect()
+    }
+}
+
+/// Times to select a second parent distinct from the first before picking one at random
+const PAIR_ATTEMPTS: usize = 8;
+
+/// Members of `genomes` by index, fittest first
+fn by_fitness<G>(genomes: &[(G, f64)]) -> Vec<usize> {
+    let mut order = (0..genomes.len()).collect::<Vec<_>>();
+    order.sort_by(|l, r| genomes[*r].1.total_cmp(&genomes[*l].1));
+    order
+}
+
+/// Pick an index at random, each as likely as its weight. An infinite weight outweighs any
+/// finite one, so is certain to be picked, or as likely as any other infinite weight. Otherwise,
+/// weights that aren't positive are never picked, unless none are, when any index is as likely
+/// as another.
+fn weighted(weights: &[f64], rng: &mut impl RngCore) -> usize {
+    let infinite = (0..weights.len())
+        .filter(|idx| weights[*idx] == f64::INFINITY)
+        .collect::<Vec<_>>();
+    if !infinite.is_empty() {
+        return infinite[rng.random_range(0..infinite.len())];
+    }
+
+    let weight = |w: f64| if w.is_finite() && w > 0. { w } else { 0. };
+    let total = weights.iter().map(|w| weight(*w)).sum::<f64>();
+    if !(total.is_finite() && total > 0.) {
+        return rng.random_range(0..weights.len());
+    }
+
+    let mut target = rng.random_range(0. ..total);
+    for (idx, w) in weights.iter().enumerate() {
+        target -= weight(*w);
+        if target < 0. {
+            return idx;
+        }
+    }
+
+    weights.iter().rposition(|w| weight(*w) > 0.).unwrap()
+}
+
+/// Eevee's historical scheme. Copies cycle through the members fittest first, and crossover
+/// cycles through every pair of members by their combined fitness, fittest first.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Ordered;
+
+impl Selection for Ordered {
+    fn select<G>(&self, genomes: &[(G, f64)], _: &mut impl RngCore) -> usize {
+        by_fitness(genomes)[0]
+    }
+
+    fn parents<G>(&self, genomes: &[(G, f64)], count: usize, _: &mut impl RngCore) -> Vec<usize> {
+        by_fitness(genomes)
+            .into_iter()
+            .cycle()
+            .take(count)
+            .collect()
     }
 
-    let pairs = {
+    fn pairs<G>(
+        &self,
+        genomes: &[(G, f64)],
+        count: usize,
+        _: &mut impl RngCore,
+    ) -> Vec<(usize, usize)> {
         let mut pairs = genomes
             .iter()
             .enumerate()
-            .flat_map(|(l_idx, (l, l_fit))| {
+            .flat_map(|(l_idx, (_, l_fit))| {
                 genomes
                     .iter()
                     .enumerate()
-                    .filter_map(move |(r_idx, (r, r_fit))| {
+                    .filter_map(move |(r_idx, (_, r_fit))| {
                         if l_fit > r_fit || (l_fit == r_fit && l_idx > r_idx) {
-                            Some(((l, l_fit), (r, r_fit)))
+                            Some(((l_idx, l_fit), (r_idx, r_fit)))
                         } else {
                             None
                         }
                     })
             })
             .collect::<Vec<_>>();
-        pairs.sort_by(|l, r| {
-            let r = r.0 .1 + r.1 .1;
-            let l = l.0 .1 + l.1 .1;
-            (r).partial_cmp(&l)
-                .unwrap_or_else(|| panic!("cannot partial_cmp {l} and {r}"))
-        });
+        pairs.sort_by(|l, r| (r.0 .1 + r.1 .1).total_cmp(&(l.0 .1 + l.1 .1)));
+
         pairs
-    };
+            .into_iter()
+            .cycle()
+            .take(count)
+            .map(|((l, _), (r, _))| (l, r))
+            .collect()
+    }
+}
+
+/// The fittest of `size` members picked at random
+#[derive(Debug, Clone, Copy)]
+pub struct Tournament {
+    pub size: usize,
+}
+
+impl Selection for Tournament {
+    fn select<G>(&self, genomes: &[(G, f64)], rng: &mut impl RngCore) -> usize {
+        (0..self.size.max(1))
+            .map(|_| rng.random_range(0..genomes.len()))
+            .max_by(|l, r| genomes[*l].1.total_cmp(&genomes[*r].1))
+            .unwrap()
+    }
+}
+
+/// Fitness-proportional selection, where each member is as likely to be picked as its fitness
+/// above the least fit member. A member of infinite fitness is always picked over finite ones.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Roulette;
+
+impl Selection for Roulette {
+    fn select<G>(&self, genomes: &[(G, f64)], rng: &mut impl RngCore) -> usize {
+        let least = genomes
+            .iter()
+            .map(|(_, fit)| *fit)
+            .filter(|fit| fit.is_finite())
+            .reduce(f64::min)
+            .unwrap_or(0.);
+        weighted(
+            &genomes
+                .iter()
+                .map(|(_, fit)| fit - least)
+                .collect::<Vec<_>>(),
+            rng,
+        )
+    }
+}
+
+/// Rank-proportional selection, where the least fit member has a weight of 1, the next 2, and
+/// so on. Unlike [Roulette], the scale of fitness doesn't matter.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Rank;
+
+impl Selection for Rank {
+    fn select<G>(&self, genomes: &[(G, f64)], rng: &mut impl RngCore) -> usize {
+        let order = by_fitness(genomes);
+        let pick = weighted(
+            &(0..order.len())
+                .map(|rank| (order.len() - rank) as f64)
+                .collect::<Vec<_>>(),
+            rng,
+        );
+        order[pick]
+    }
+}
+
+/// Only the fittest `survival` portion of members ( at least 1 ) may be parents, each as likely
+/// as another
+#[derive(Debug, Clone, Copy)]
+pub struct Truncation {
+    pub survival: f64,
+}
+
+impl Selection for Truncation {
+    fn select<G>(&self, genomes: &[(G, f64)], rng: &mut impl RngCore) -> usize {
+        let survivors =
+            ((genomes.len() as f64 * self.survival).ceil() as usize).clamp(1, genomes.len());
+        by_fitness(genomes)[rng.random_range(0..survivors)]
+    }
+}
+
+impl Selection for SelectionStrategy {
+    fn select<G>(&self, genomes: &[(G, f64)], rng: &mut impl RngCore) -> usize {
+        match *self {
+            Self::Ordered => Ordered.select(genomes, rng),
+            Self::Tournament { size } => Tournament { size }.select(genomes, rng),
+            Self::Roulette => Roulette.select(genomes, rng),
+            Self::Rank => Rank.select(genomes, rng),
+            Self::Truncation { survival } => Truncation { survival }.select(genomes, rng),
+        }
+    }
+
+    fn parents<G>(&self, genomes: &[(G, f64)], count: usize, rng: &mut impl RngCore) -> Vec<usize> {
+        match *self {
+            Self::Ordered => Ordered.parents(genomes, count, rng),
+            Self::Tournament { size } => Tournament { size }.parents(genomes, count, rng),
+            Self::Roulette => Roulette.parents(genomes, count, rng),
+            Self::Rank => Rank.parents(genomes, count, rng),
+            Self::Truncation { survival } => Truncation { survival }.parents(genomes, count, rng),
+        }
+    }
+
+    fn pairs<G>(
+        &self,
+        genomes: &[(G, f64)],
+        count: usize,
+        rng: &mut impl RngCore,
+    ) -> Vec<(usize, usize)> {
+        match *self {
+            Self::Ordered => Ordered.pairs(genomes, count, rng),
+            Self::Tournament { size } => Tournament { size }.pairs(genomes, count, rng),
+            Self::Roulette => Roulette.pairs(genomes, count, rng),
+            Self::Rank => Rank.pairs(genomes, count, rng),
+            Self::Truncation { survival } => Truncation { survival }.pairs(genomes, count, rng),
+        }
+    }
+}
+
+fn reproduce_crossover<C: Connection, G: Genome<C>>(
+    genomes: &[(G, f64)],
+    size: usize,
+    rng: &mut impl RngCore,
+    innogen: &mut InnoGen,
+    config: &EvolutionConfig,
+    selection: &impl Selection,
+) -> Result<Vec<G>, EvolutionError> {
+    if size == 0 {
+        return Ok(vec![]);
+    }
+
+    if genomes.len() < 2 {
+        return Err(EvolutionError::TooFewMembers {
+            produce: size,
+            members: genomes.len(),
+        });
+    }
 
-    pairs
+    selection
+        .pairs(genomes, size, rng)
         .into_iter()
-        .cycle()
-        .take(size)
-        .map(|((l, _), (r, _))| {
-            let mut child = l.reproduce_with(r, std::cmp::Ordering::Greater, rng);
-            child.mutate(rng, innogen);
+        .map(|(l, r)| {
+            let mut child =
+                genomes[l]
+                    .0
+                    .reproduce_with(&genomes[r].0, std::cmp::Ordering::Greater, rng);
+            child.mutate(rng, innogen, &config.mutation)?;
             Ok(child)
         })
         .collect()
@@ -69,63 +284,90 @@ fn reproduce_copy<C: Connection, G: Genome<C>>(
     size: usize,
     rng: &mut impl RngCore,
     innogen: &mut InnoGen,
-) -> Result<Vec<G>, Box<dyn Error>> {
+    config: &EvolutionConfig,
+    selection: &impl Selection,
+) -> Result<Vec<G>, EvolutionError> {
     if size == 0 {
         return Ok(vec![]);
     }
 
     if genomes.is_empty() {
-        return Err(format!(
-            "too few members to copy (wanted to produce {size} from {}",
-            genomes.len()
-        )
-        .into());
+        return Err(EvolutionError::TooFewMembers {
+            produce: size,
+            members: 0,
+        });
     }
 
-    let mut top = genomes.iter().collect::<Vec<_>>();
-    top.sort_by(|(_, l), (_, r)| {
-        r.partial_cmp(l)
-            .unwrap_or_else(|| panic!("cannot partial_cmp {l} and {r}"))
-    });
-    top.into_iter()
-        .cycle()
-        .take(size)
-        .map(|(genome, _)| {
-            let mut child = genome.clone();
-            child.mutate(rng, innogen);
+    selection
+        .parents(genomes, size, rng)
+        .into_iter()
+        .map(|idx| {
+            let mut child = genomes[idx].0.clone();
+            child.mutate(rng, innogen, &config.mutation)?;
             Ok(child)
         })
         .collect()
 }
 
+/// Reproduce `size` children from `genomes`, picking parents by the configured
+/// [selection](EvolutionConfig::selection). See [reproduce_by].
 pub fn reproduce<C: Connection, G: Genome<C>>(
     genomes: Vec<(G, f64)>,
     size: usize,
     innogen: &mut InnoGen,
     rng: &mut impl RngCore,
-) -> Result<Vec<G>, Box<dyn Error>> {
+    config: &EvolutionConfig,
+) -> Result<Vec<G>, EvolutionError> {
+    reproduce_by(genomes, size, innogen, rng, config, &config.selection)
+}
+
+/// Reproduce `size` children from `genomes`, picking parents by `selection`. The fittest
+/// [elitism](EvolutionConfig::elitism) genomes are kept as-is, and the rest are split between
+/// mutated copies and crossover children by [crossover_rate](EvolutionConfig::crossover_rate).
+/// Fails if any fitness is NaN, or if any child can't be mutated.
+pub fn reproduce_by<C: Connection, G: Genome<C>>(
+    genomes: Vec<(G, f64)>,
+    size: usize,
+    innogen: &mut InnoGen,
+    rng: &mut impl RngCore,
+    config: &EvolutionConfig,
+    selection: &impl Selection,
+) -> Result<Vec<G>, EvolutionError> {
     if size == 0 {
         return Ok(vec![]);
     }
 
     if genomes.is_empty() {
-        return Err(format!(
-            "too few members to reproduce (wanted to produce {size} from {}",
-            genomes.len()
-        )
-        .into());
+        return Err(EvolutionError::TooFewMembers {
+            produce: size,
+            members: 0,
+        });
     }
 
-    let mut pop: Vec<G> = Vec::with_capacity(size);
-    pop.push(genomes.fittest().unwrap().0.clone());
+    if let Some((_, fit)) = genomes.iter().find(|(_, fit)| fit.is_nan()) {
+        return Err(EvolutionError::Incomparable(*fit));
+    }
 
-    if size == 1 {
+    // fittest first, and of those equally fit, the later first
+    let mut elites = (0..genomes.len()).collect::<Vec<_>>();
+    elites.sort_by(|l, r| genomes[*r].1.total_cmp(&genomes[*l].1).then(r.cmp(l)));
+
+    let mut pop: Vec<G> = Vec::with_capacity(size);
+    pop.extend(
+        elites
+            .into_iter()
+            .take(config.elitism.min(size))
+            .map(|idx| genomes[idx].0.clone()),
+    );
+
+    if size == pop.len() {
         return Ok(pop);
     }
 
-    let size = size - 1;
-    let size_copy = size / 4;
-    let size_copy = if size_copy == 0 || genomes.len() == 1 {
+    let size = size - pop.len();
+    let crossover_rate = config.crossover_rate.clamp(0., 1.);
+    let size_copy = (size as f64 * (1. - crossover_rate)) as usize;
+    let size_copy = if genomes.len() == 1 || (size_copy == 0 && crossover_rate < 1.) {
         size
     } else {
         size_copy
@@ -133,35 +375,108 @@ pub fn reproduce<C: Connection, G: Genome<C>>(
 
     // TODO reproduce_crossover and reproduce_copy can potentially be made faster
     // if they're handed a slice to write into intead of returning a vec that we then need to copy
-    reproduce_copy(&genomes, size_copy, rng, innogen)?
+    reproduce_copy(&genomes, size_copy, rng, innogen, config, selection)?
         .into_iter()
         .for_each(|genome| pop.push(genome));
 
     let size_crossover = size - size_copy;
-    reproduce_crossover(&genomes, size_crossover, rng, innogen)?
+    reproduce_crossover(&genomes, size_crossover, rng, innogen, config, selection)?
         .into_iter()
         .for_each(|genome| pop.push(genome));
 
     Ok(pop)
 }
 
+/// Split `population` in proportion to `shares`, which are non-negative and finite. Each gets
+/// the whole part of its portion, and whoever is left over goes one each to those with the
+/// largest remainders, earlier first among equals. Nobody gets anything if every share is 0.
+fn largest_remainder(shares: &[f64], population: usize) -> Vec<usize> {
+    let total = shares.iter().sum::<f64>();
+    if total <= 0. {
+        return vec![0; shares.len()];
+    }
+
+    let portions = shares
+        .iter()
+        .map(|share| population as f64 * share / total)
+        .collect::<Vec<_>>();
+    let mut alloc = portions
+        .iter()
+        .map(|portion| portion.floor() as usize)
+        .collect::<Vec<_>>();
+
+    let mut order = (0..shares.len()).collect::<Vec<_>>();
+    order.sort_by(|l, r| {
+        (portions[*r] - portions[*r].floor()).total_cmp(&(portions[*l] - portions[*l].floor()))
+    });
+    // float error may leave the floored portions a little over or under
+    let placed = alloc.iter().sum::<usize>();
+    if placed < population {
+        for idx in order.iter().cycle().take(population - placed) {
+            alloc[*idx] += 1;
+        }
+    } else {
+        for idx in order.iter().rev().cycle().take(placed - population) {
+            alloc[*idx] -= 1;
+        }
+    }
+
+    alloc
+}
+
+/// Share of the population that each specie is due, by its adjusted fitness
+/// - empty species are due nothing
+/// - species whose adjusted fitness is NaN or negative infinity are due nothing
+/// - if any species' adjusted fitness is positive infinity, only they are due anything
+/// - if any are negative, every adjusted fitness is raised so that the least is 0
+/// - if nobody is due anything after all, every non-empty specie is due the same
+pub fn specie_shares<C: Connection, G: Genome<C>>(species: &[Specie<C, G>]) -> Vec<f64> {
+    let fitted = species
+        .iter()
+        .map(|s| {
+            let fit = s.fit_adjusted();
+            if s.is_empty() || fit.is_nan() || fit == f64::NEG_INFINITY {
+                None
+            } else {
+                Some(fit)
+            }
+        })
+        .collect::<Vec<_>>();
+
+    let shares = if fitted.contains(&Some(f64::INFINITY)) {
+        fitted
+            .iter()
+            .map(|fit| if *fit == Some(f64::INFINITY) { 1. } else { 0. })
+            .collect::<Vec<_>>()
+    } else {
+        let least = fitted
+            .iter()
+            .flatten()
+            .fold(0., |acc: f64, fit| acc.min(*fit));
+        fitted
+            .iter()
+            .map(|fit| fit.map_or(0., |fit| fit - least))
+            .collect::<Vec<_>>()
+    };
+
+    if shares.iter().sum::<f64>() > 0. {
+        shares
+    } else {
+        species
+            .iter()
+            .map(|s| if s.is_empty() { 0. } else { 1. })
+            .collect()
+    }
+}
+
+/// Allocate exactly `population` members between `species`, in proportion to their
+/// [shares](specie_shares). Only if every specie is empty is nobody allocated.
 pub fn population_alloc<'a, C: Connection + 'a, G: Genome<C> + 'a>(
     species: Vec<Specie<C, G>>,
     population: usize,
 ) -> impl Iterator<Item = (Specie<C, G>, usize)> {
-    let species_fitted = species.iter().map(|s| s.fit_adjusted()).collect::<Vec<_>>();
-    let fit_total = species_fitted.iter().sum::<f64>();
-
-    let population_f = population as f64;
-    species
-        .into_iter()
-        .zip(species_fitted)
-        .map(move |(specie, fit_adjusted)| {
-            (
-                specie,
-                f64::round(population_f * fit_adjusted / fit_total) as usize,
-            )
-        })
+    let alloc = largest_remainder(&specie_shares(&species), population);
+    species.into_iter().zip(alloc)
 }
 
 // reproduce a whole speciated population into a non-speciated population
@@ -170,30 +485,35 @@ pub fn population_reproduce<C: Connection, G: Genome<C>>(
     population: usize,
     inno_head: usize,
     rng: &mut impl RngCore,
-) -> (Vec<G>, usize) {
+    config: &EvolutionConfig,
+) -> Result<(Vec<G>, usize), EvolutionError> {
     let mut innogen = InnoGen::new(inno_head);
+    let pop = population_reproduce_with(species, population, &mut innogen, rng, config)?;
+    Ok((pop, innogen.head))
+}
 
-    // Inline population_alloc logic
-    let species_fitted = species.iter().map(|s| s.fit_adjusted()).collect::<Vec<_>>();
-    let fit_total = species_fitted.iter().sum::<f64>();
-    let population_f = population as f64;
-
-    let allocated = species
-        .iter()
-        .zip(species_fitted)
-        .map(|(specie, fit_adjusted)| {
-            (
-                specie.members.clone(),
-                f64::round(population_f * fit_adjusted / fit_total) as usize,
-            )
-        });
+/// Like [population_reproduce], but drawing innovation ids from an existing [InnoGen], so that
+/// several populations reproducing in the same generation may share them
+pub fn population_reproduce_with<C: Connection, G: Genome<C>>(
+    species: &[Specie<C, G>],
+    population: usize,
+    innogen: &mut InnoGen,
+    rng: &mut impl RngCore,
+    config: &EvolutionConfig,
+) -> Result<Vec<G>, EvolutionError> {
+    let alloc = largest_remainder(&specie_shares(species), population);
+    let mut pop = Vec::with_capacity(population);
+    for (specie, size) in species.iter().zip(alloc) {
+        pop.extend(reproduce(
+            specie.members.clone(),
+            size,
+            innogen,
+            rng,
+            config,
+        )?);
+    }
 
-    (
-        allocated
-            .flat_map(|(members, pop)| reproduce(members, pop, &mut innogen, rng).unwrap())
-            .collect::<Vec<_>>(),
-        innogen.head,
-    )
+    Ok(pop)
 }
 
 #[cfg(test)]
@@ -202,9 +522,10 @@ mod test {
     use crate::{
         genome::{Recurrent, WConnection},
         population::{population_init, SpecieRepr},
-        random::default_rng,
+        random::{default_rng, WyRng},
         test_t,
     };
+    use rand::Rng;
 
     #[test]
     fn test_inno_gen() {
@@ -236,7 +557,8 @@ mod test {
                         specie.members.clone(),
                         i,
                         &mut InnoGen::new(inno_head),
-                        &mut rng
+                        &mut rng,
+                        &EvolutionConfig::default(),
                     )
                     .unwrap()
                     .len()
@@ -266,6 +588,10 @@ mod test {
                     )
                 })
                 .collect(),
+            id: 0,
+            born: 0,
+            improved: 0,
+            best: f64::MIN,
         };
 
         let connection_2 = C::new(3, 4, &mut InnoGen::new(1));
@@ -284,6 +610,10 @@ mod test {
                     )
                 })
                 .collect(),
+            id: 1,
+            born: 0,
+            improved: 0,
+            best: f64::MIN,
         };
 
         let adjusted_1 = specie_1.fit_adjusted();
@@ -312,4 +642,247 @@ mod test {
             }
         }
     }
+
+    /// How many times each member of `fitness` is picked in `count` selections
+    fn tally(selection: &impl Selection, fitness: &[f64], count: usize) -> Vec<usize> {
+        let genomes = fitness.iter().map(|f| ((), *f)).collect::<Vec<_>>();
+        let mut tally = vec![0; genomes.len()];
+        for idx in selection.parents(&genomes, count, &mut WyRng::seeded(0)) {
+            tally[idx] += 1;
+        }
+        tally
+    }
+
+    #[test]
+    fn test_selection() {
+        let fitness = [1., 4., 2., 3.];
+
+        assert_eq!(tally(&Ordered, &fitness, 6), [1, 2, 1, 2]);
+        assert_eq!(
+            tally(&Tournament { size: 100 }, &fitness, 100),
+            [0, 100, 0, 0]
+        );
+        let truncated = tally(&Truncation { survival: 0.5 }, &fitness, 1000);
+        assert_eq!(truncated[0] + truncated[2], 0);
+        assert!(truncated[1] > 400 && truncated[3] > 400);
+
+        // the least fit is never picked, and the rest in proportion to how much fitter they are
+        let roulette = tally(&Roulette, &[-1., 2., 0., 1.], 6000);
+        assert_eq!(roulette[0], 0);
+        assert!(roulette[1] > roulette[3] && roulette[3] > roulette[2]);
+        assert!(roulette[1].abs_diff(3000) < 300);
+
+        let rank = tally(&Rank, &fitness, 10000);
+        assert!(rank[1] > rank[3] && rank[3] > rank[2] && rank[2] > rank[0]);
+        assert!(rank[0].abs_diff(1000) < 200);
+
+        // equal fitness leaves nobody to prefer
+        assert!(tally(&Roulette, &[2., 2.], 100).iter().all(|t| *t > 30));
+
+        // an infinitely fit member is always picked, and shares that only with its equals
+        let infinite = tally(&Roulette, &[1., f64::INFINITY, 3., f64::NEG_INFINITY], 100);
+        assert_eq!(infinite, [0, 100, 0, 0]);
+        let infinite = tally(
+            &Roulette,
+            &[f64::INFINITY, f64::NEG_INFINITY, f64::INFINITY],
+            100,
+        );
+        assert!(infinite[1] == 0 && infinite[0] > 30 && infinite[2] > 30);
+    }
+
+    #[test]
+    fn test_selection_pairs() {
+        let genomes = [1., 4., 2., 3.].map(|f| ((), f));
+        let mut rng = WyRng::seeded(0);
+        for selection in [
+            SelectionStrategy::Ordered,
+            SelectionStrategy::Tournament { size: 4 },
+            SelectionStrategy::Roulette,
+            SelectionStrategy::Rank,
+            SelectionStrategy::Truncation { survival: 0.25 },
+        ] {
+            for (l, r) in selection.pairs(&genomes, 50, &mut rng) {
+                assert_ne!(l, r, "{selection:?}");
+                assert!(genomes[l].1 >= genomes[r].1, "{selection:?}");
+            }
+        }
+    }
+
+    test_t!(test_elitism[T: G]() {
+        let (mut genome, inno_head) = T::new(2, 1);
+        let members = (0..5)
+            .map(|idx| {
+                genome.connections_mut()[0].weight = idx as f64;
+                (genome.clone(), idx as f64)
+            })
+            .collect::<Vec<_>>();
+
+        for elitism in [0, 1, 3, 10] {
+            let config = EvolutionConfig {
+                elitism,
+                selection: SelectionStrategy::Tournament { size: 2 },
+                ..EvolutionConfig::default()
+            };
+            let children = reproduce(
+                members.clone(),
+                8,
+                &mut InnoGen::new(inno_head),
+                &mut WyRng::seeded(0),
+                &config,
+            )
+            .unwrap();
+            assert_eq!(children.len(), 8);
+
+            let kept = elitism.min(members.len()).min(8);
+            for (child, weight) in children.iter().zip([4., 3., 2., 1., 0.]).take(kept) {
+                assert_eq!(child.connections()[0].weight, weight);
+            }
+        }
+    });
+
+    fn specie(fitness: &[f64]) -> Specie<C, G> {
+        let (genome, _) = G::new(2, 1);
+        Specie {
+            members: fitness.iter().map(|f| (genome.clone(), *f)).collect(),
+            ..Specie::new(SpecieRepr::new(genome.connections().to_vec()), 0, 0)
+        }
+    }
+
+    fn alloc(fitness: &[&[f64]], population: usize) -> Vec<usize> {
+        population_alloc(fitness.iter().map(|f| specie(f)).collect(), population)
+            .map(|(_, alloc)| alloc)
+            .collect()
+    }
+
+    #[test]
+    fn test_population_alloc_exact() {
+        // rounding would give 33 each, and 34 each for 101
+        assert_eq!(alloc(&[&[1.], &[1.], &[1.]], 100), [34, 33, 33]);
+        assert_eq!(alloc(&[&[1.], &[1.], &[1.]], 101), [34, 34, 33]);
+        assert_eq!(alloc(&[&[1.], &[2.], &[3.], &[4.]], 7), [1, 1, 2, 3]);
+
+        let mut rng = WyRng::seeded(0);
+        for population in [0, 1, 7, 50, 150, 1000] {
+            let fitness = (0..rng.random_range(1..12))
+                .map(|_| vec![rng.random_range(0. ..100.); rng.random_range(1..5)])
+                .collect::<Vec<_>>();
+            let fitness = fitness.iter().map(Vec::as_slice).collect::<Vec<_>>();
+            assert_eq!(
+                alloc(&fitness, population).iter().sum::<usize>(),
+                population
+            );
+        }
+    }
+
+    #[test]
+    fn test_population_alloc_negative() {
+        // shifted to 0, 2, and 3
+        assert_eq!(alloc(&[&[-2.], &[0.], &[1.]], 50), [0, 20, 30]);
+        assert_eq!(alloc(&[&[-4.], &[-2.]], 10), [0, 10]);
+        assert_eq!(alloc(&[&[-3.]], 10), [10]);
+    }
+
+    #[test]
+    fn test_population_alloc_zero() {
+        assert_eq!(alloc(&[&[0.], &[0.], &[0.]], 10), [4, 3, 3]);
+        assert_eq!(alloc(&[&[5.], &[5.]], 9), [5, 4]);
+        assert_eq!(alloc(&[&[0., 0.], &[], &[0.]], 10), [5, 0, 5]);
+        assert_eq!(alloc(&[&[], &[]], 10), [0, 0]);
+        assert_eq!(alloc(&[], 10), Vec::<usize>::new());
+    }
+
+    #[test]
+    fn test_population_alloc_non_finite() {
+        assert_eq!(alloc(&[&[f64::NAN], &[1.], &[3.]], 20), [0, 5, 15]);
+        assert_eq!(
+            alloc(&[&[f64::NEG_INFINITY], &[-1.], &[1.]], 20),
+            [0, 0, 20]
+        );
+        assert_eq!(
+            alloc(&[&[f64::INFINITY], &[1.], &[f64::INFINITY]], 9),
+            [5, 0, 4]
+        );
+        assert_eq!(alloc(&[&[f64::NAN], &[f64::NAN]], 9), [5, 4]);
+        assert_eq!(
+            alloc(&[&[f64::INFINITY, f64::NEG_INFINITY], &[1.]], 9),
+            [0, 9]
+        );
+    }
+
+    #[test]
+    fn test_population_reproduce_exact() {
+        let species = [
+            specie(&[1., 2.]),
+            specie(&[f64::NAN]),
+            specie(&[-1., 0.]),
+            specie(&[]),
+        ];
+        for population in [0, 1, 9, 10, 33] {
+            let (pop, _) = population_reproduce(
+                &species,
+                population,
+                1,
+                &mut WyRng::seeded(0),
+                &EvolutionConfig::default(),
+            )
+            .unwrap();
+            assert_eq!(pop.len(), population);
+        }
+    }
+
+    test_t!(test_reproduce_errors[T: G]() {
+        let mut rng = default_rng();
+        let (genome, inno_head) = T::new(2, 1);
+        let config = EvolutionConfig::default();
+        let mut innogen = InnoGen::new(inno_head);
+
+        assert_eq!(
+            reproduce::<C, T>(vec![], 3, &mut innogen, &mut rng, &config).err(),
+            Some(EvolutionError::TooFewMembers { produce: 3, members: 0 })
+        );
+        assert_eq!(
+            reproduce_crossover(
+                &[(genome.clone(), 1.)],
+                3,
+                &mut rng,
+                &mut innogen,
+                &config,
+                &Ordered,
+            )
+            .err(),
+            Some(EvolutionError::TooFewMembers { produce: 3, members: 1 })
+        );
+        assert!(matches!(
+            reproduce(
+                vec![(genome.clone(), 1.), (genome.clone(), f64::NAN)],
+                3,
+                &mut innogen,
+                &mut rng,
+                &config,
+            ),
+            Err(EvolutionError::Incomparable(fit)) if fit.is_nan()
+        ));
+
+        // nothing to produce is never an error
+        assert!(reproduce(vec![(genome.clone(), f64::NAN)], 0, &mut innogen, &mut rng, &config)
+            .unwrap()
+            .is_empty());
+
+        // crossover rates outside of 0..=1 are clamped into it
+        for crossover_rate in [-1., 2.] {
+            let config = EvolutionConfig {
+                crossover_rate,
+                ..EvolutionConfig::default()
+            };
+            let pop = reproduce(
+                vec![(genome.clone(), 1.), (genome.clone(), 2.)],
+                5,
+                &mut innogen,
+                &mut rng,
+                &config,
+            )
+            .unwrap();
+            assert_eq!(pop.len(), 5);
+        }
+    });
 }
@@ -1,29 +1,131 @@
 //! Traits related to evaluation, fitting, and evolution of genomes for specific tasks.
 
+#[cfg(feature = "parallel")]
+use crate::population::ParallelFirstMatch;
 use crate::{
-    genome::Genome,
-    population::{speciate, Specie, SpecieRepr},
-    reproduce::population_reproduce,
+    config::{EvolutionConfig, FitnessPolicy, SpeciationStrategy},
+    error::EvolutionError,
+    genome::{Genome, InnoGen},
+    hall::HallOfFame,
+    novelty::Archive,
+    pareto,
+    population::{speciate_into, Speciation, Specie},
+    reproduce::population_reproduce_with,
     Connection,
 };
-use core::{f64, ops::ControlFlow};
+use core::{cmp::Ordering, error::Error, f64, mem::take, ops::ControlFlow};
 use rand::RngCore;
 #[cfg(feature = "parallel")]
 use rayon::{
     iter::{IntoParallelIterator, ParallelIterator},
-    ThreadPoolBuilder,
+    ThreadPool, ThreadPoolBuilder,
+};
+use serde::{Deserialize, Serialize};
+use std::{
+    fs,
+    path::Path,
+    time::{Duration, Instant},
 };
-use std::collections::HashMap;
 
-const NO_IMPROVEMENT_TRUNCATE: usize = 10;
+/// Wall-clock time spent on each stage of a generation
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct Timings {
+    /// Evaluating every genome, which is zero if they were [told](Evolver::tell) their fitness
+    pub evaluation: Duration,
+    /// Speciating the evaluated genomes
+    pub speciation: Duration,
+    /// Reproducing the last generation into this one
+    pub reproduction: Duration,
+}
 
 /// Stats passed to a hook fn
 pub struct Stats<'a, C: Connection, G: Genome<C>> {
     pub generation: usize,
     pub species: &'a [Specie<C, G>],
+    /// Objectives of each member of each specie, if evaluated by a [MultiObjective] scenario
+    pub objectives: &'a [Vec<Vec<f64>>],
+    /// Novel behaviors seen so far, if evaluated by a [BehaviorScenario]
+    pub archive: &'a Archive,
+    /// Fittest distinct genomes seen so far, including those of this generation
+    pub hall_of_fame: &'a HallOfFame<G>,
+    /// Compatibility threshold that this generation was speciated with
+    pub threshold: f64,
+    /// Policy that fitnesses which weren't finite were handled by
+    pub fitness_policy: FitnessPolicy,
+    /// Number of genomes of this generation whose fitness ( or any objective ) wasn't finite
+    pub non_finite: usize,
+    pub timings: Timings,
+    /// Number of innovation ids first handed out while reproducing the last generation into
+    /// this one
+    pub innovations: usize,
+    /// Next innovation id to be handed out
+    pub inno_head: usize,
+    /// Greatest fitness ever achieved by any specie, or of its first objective if evaluated by
+    /// a [MultiObjective] scenario. Evaluated by a [BehaviorScenario], it's the fitness before
+    /// any blending with novelty.
+    pub best: Option<f64>,
 }
 
 impl<C: Connection, G: Genome<C>> Stats<'_, C, G> {
+    fn members(&self) -> impl Iterator<Item = &(G, f64)> {
+        self.species
+            .iter()
+            .flat_map(|Specie { members, .. }| members.iter())
+    }
+
+    fn mean(values: impl Iterator<Item = f64>) -> Option<f64> {
+        let (sum, count) = values.fold((0., 0), |(sum, count), v| (sum + v, count + 1));
+        (count != 0).then(|| sum / count as f64)
+    }
+
+    /// Mean fitness of every member, which are ranks if evaluated by a [MultiObjective]
+    /// scenario
+    pub fn fitness_mean(&self) -> Option<f64> {
+        Self::mean(self.members().map(|(_, fit)| *fit))
+    }
+
+    pub fn fitness_median(&self) -> Option<f64> {
+        let mut fitness = self.members().map(|(_, fit)| *fit).collect::<Vec<_>>();
+        fitness.sort_by(f64::total_cmp);
+        let mid = fitness.len() / 2;
+        match fitness.len() {
+            0 => None,
+            len if len % 2 == 0 => Some((fitness[mid - 1] + fitness[mid]) / 2.),
+            _ => Some(fitness[mid]),
+        }
+    }
+
+    /// Standard deviation of the fitness of every member
+    pub fn fitness_stddev(&self) -> Option<f64> {
+        let mean = self.fitness_mean()?;
+        Self::mean(self.members().map(|(_, fit)| (fit - mean).powi(2))).map(f64::sqrt)
+    }
+
+    pub fn nodes_mean(&self) -> Option<f64> {
+        Self::mean(self.members().map(|(g, _)| g.nodes().len() as f64))
+    }
+
+    /// Mean number of connections of every member, including those that are disabled
+    pub fn connections_mean(&self) -> Option<f64> {
+        Self::mean(self.members().map(|(g, _)| g.connections().len() as f64))
+    }
+
+    /// Generations since each specie was formed
+    pub fn ages(&self) -> Vec<usize> {
+        self.species
+            .iter()
+            .map(|s| s.age(self.generation))
+            .collect()
+    }
+
+    /// Generations since each specie last improved
+    pub fn stagnation(&self) -> Vec<usize> {
+        self.species
+            .iter()
+            .map(|s| s.stagnant(self.generation))
+            .collect()
+    }
+
     pub fn any_fitter_than(&self, target: f64) -> bool {
         self.species
             .iter()
@@ -34,10 +136,32 @@ impl<C: Connection, G: Genome<C>> Stats<'_, C, G> {
         self.species
             .iter()
             .flat_map(|Specie { members, .. }| members.iter())
-            .max_by(|(_, l), (_, r)| {
-                l.partial_cmp(r)
-                    .unwrap_or_else(|| panic!("cannot partial_cmp {l} and {r}"))
+            .max_by(|(_, l), (_, r)| l.total_cmp(r))
+    }
+
+    /// Genomes who aren't dominated by any other, along with their objectives. Empty unless
+    /// evaluated by a [MultiObjective] scenario.
+    pub fn pareto_front(&self) -> Vec<(&G, &[f64])> {
+        let members = self
+            .species
+            .iter()
+            .zip(self.objectives)
+            .flat_map(|(Specie { members, .. }, objectives)| {
+                members
+                    .iter()
+                    .zip(objectives)
+                    .map(|((genome, _), objectives)| (genome, objectives.as_slice()))
             })
+            .collect::<Vec<_>>();
+
+        let objectives = members
+            .iter()
+            .map(|(_, objectives)| objectives.to_vec())
+            .collect::<Vec<_>>();
+        match pareto::fronts(&objectives).into_iter().next() {
+            Some(front) => front.into_iter().map(|idx| members[idx]).collect(),
+            None => Vec::new(),
+        }
     }
 }
 
@@ -71,142 +195,1583 @@ impl<C: Connection, G: Genome<C>> EvolutionHooks<C, G> {
 /// Scenario describes the setting in which evolution takes place. For any genome kind,
 /// (eval)[Scenario::eval] should be implemented such that it evaluates the genome ( or a
 /// network that it produces ) with some fitness. Greater fitnesses will be optimized for
-pub trait Scenario<C: Connection, G: Genome<C>, A: Fn(f64) -> f64> {
+pub trait Scenario<C: Connection, G: Genome<C>> {
     fn io(&self) -> (usize, usize);
-    fn eval(&self, genome: &G, σ: &A) -> f64;
+    fn eval(&self, genome: &G) -> f64;
+}
+
+/// Like a [Scenario], but evaluating a genome by several objectives at once ( say, some task
+/// score, and the size of its network ) rather than a single fitness. Every genome should be
+/// evaluated with the same number of objectives, each of which will be optimized for.
+///
+/// Within each specie, members are ranked by non-dominated sorting and crowding distance ( see
+/// [pareto] ) into a fitness used for selection and reproduction. Whether a specie is
+/// stagnating is judged by its first objective alone, so it's a good place for the task score.
+pub trait MultiObjective<C: Connection, G: Genome<C>> {
+    fn io(&self) -> (usize, usize);
+    fn eval(&self, genome: &G) -> Vec<f64>;
+}
+
+/// Like a [Scenario], but evaluating a genome by a fitness along with some description of how
+/// it behaved, for novelty search ( see [novelty](crate::novelty) ). Every behavior should be
+/// the same length, and behaviors who are further apart ( by euclidean distance ) should be
+/// more different in some way that matters for the task.
+///
+/// Each genome is then scored by its sparseness among the behaviors of its generation and of
+/// the archive, which is blended into its fitness by
+/// [blend](crate::config::NoveltyConfig::blend).
+pub trait BehaviorScenario<C: Connection, G: Genome<C>> {
+    fn io(&self) -> (usize, usize);
+    fn eval(&self, genome: &G) -> (f64, Vec<f64>);
+}
+
+/// A stepwise handle on the evolution of a population. Where [evolve] owns the whole generation
+/// loop, an Evolver hands control back between each stage so that evaluation may happen
+/// elsewhere ( in another process, interleaved with some UI, etc ).
+///
+/// Each generation goes roughly like
+/// - [ask](Evolver::ask) for the genomes who need a fitness
+/// - [tell](Evolver::tell) their fitnesses, in the same order, which speciates them
+/// - [step](Evolver::step) to reproduce the evaluated generation into the next
+///
+/// If the genomes can be evaluated by a [Scenario], [eval](Evolver::eval) does the first 2.
+///
+/// An Evolver whose rng is serializable ( like [WyRng](crate::random::WyRng) ) is itself a
+/// checkpoint of the whole evolution state, and may be written with
+/// [to_file](Evolver::to_file) at any point and resumed with [from_file](Evolver::from_file).
+/// Given the same rng, a resumed evolver continues exactly as the original would have.
+#[derive(Serialize, Deserialize)]
+#[serde(bound(serialize = "R: Serialize", deserialize = "R: Deserialize<'de>"))]
+pub struct Evolver<C: Connection, G: Genome<C>, R: RngCore> {
+    population: usize,
+    pending: Vec<G>,
+    species: Vec<Specie<C, G>>,
+    inno_head: usize,
+    rng: R,
+    config: EvolutionConfig,
+    generation: usize,
+    /// species of the last generation without their members, whose identities carry on into
+    /// the next. Kept ordered like the species it was made from so that speciation is
+    /// reproducible.
+    lineage: Vec<Specie<C, G>>,
+    /// id of the next specie to form
+    #[serde(default)]
+    specie_head: usize,
+    /// objectives of each member of each specie, when evaluated by several
+    #[serde(default)]
+    objectives: Vec<Vec<Vec<f64>>>,
+    /// novel behaviors seen so far, when evaluated by behavior
+    #[serde(default)]
+    archive: Archive,
+    #[serde(default)]
+    hall_of_fame: HallOfFame<G>,
+    /// compatibility threshold, when adjusted by a controller
+    #[serde(default)]
+    threshold: Option<f64>,
+    /// genomes of the last evaluated generation whose fitness wasn't finite
+    #[serde(default)]
+    non_finite: usize,
+    /// wall-clock time isn't part of the evolution state, and would keep a resumed evolver
+    /// from ever matching the original
+    #[serde(skip)]
+    timings: Timings,
+    /// innovation ids first handed out by the last reproduction
+    #[serde(default)]
+    innovations: usize,
+    /// greatest fitness ever achieved by any specie
+    #[serde(default)]
+    best: Option<f64>,
+    #[cfg(feature = "parallel")]
+    #[serde(skip, default = "thread_pool")]
+    thread_pool: ThreadPool,
+}
+
+/// A generation reproduced by an [Evolver], waiting to be committed to it
+pub(crate) struct Staged<G> {
+    pending: Vec<G>,
+    threshold: Option<f64>,
+    inno_head: usize,
+    innovations: usize,
+    reproduction: Duration,
+}
+
+/// An evaluated generation of an [Evolver], as it was before any immigration
+pub(crate) struct Evaluated<C: Connection, G: Genome<C>> {
+    species: Vec<Specie<C, G>>,
+    specie_head: usize,
+    objectives: Vec<Vec<Vec<f64>>>,
+    best: Option<f64>,
+}
+
+#[cfg(feature = "parallel")]
+fn thread_pool() -> ThreadPool {
+    ThreadPoolBuilder::new().build().unwrap()
+}
+
+/// Speciation by some strategy, which if [Parallel](SpeciationStrategy::Parallel) is done by
+/// [ParallelFirstMatch]
+#[cfg(feature = "parallel")]
+struct Parallelized(SpeciationStrategy);
+
+#[cfg(feature = "parallel")]
+impl<C: Connection + Sync, G: Genome<C> + Send + Sync> Speciation<C, G> for Parallelized {
+    fn speciate(
+        &self,
+        genomes: Vec<(G, f64)>,
+        lineage: Vec<Specie<C, G>>,
+        threshold: f64,
+        specie_head: &mut usize,
+        generation: usize,
+        rng: &mut impl RngCore,
+    ) -> Vec<Specie<C, G>> {
+        match self.0 {
+            SpeciationStrategy::Parallel => ParallelFirstMatch.speciate(
+                genomes,
+                lineage,
+                threshold,
+                specie_head,
+                generation,
+                rng,
+            ),
+            strategy => {
+                strategy.speciate(genomes, lineage, threshold, specie_head, generation, rng)
+            }
+        }
+    }
+}
+
+impl<C: Connection, G: Genome<C>, R: RngCore> Evolver<C, G, R> {
+    /// Start evolving from some initial population, like the one returned from
+    /// [population_init](crate::population::population_init). Population size is inferred from
+    /// the number of initial genomes, and their fitness is disregarded.
+    pub fn new(
+        (species, inno_head): (Vec<Specie<C, G>>, usize),
+        config: EvolutionConfig,
+        rng: R,
+    ) -> Self {
+        let pending = species
+            .into_iter()
+            .flat_map(|Specie { members, .. }| members.into_iter().map(|(genome, _)| genome))
+            .collect::<Vec<_>>();
+
+        Self {
+            population: pending.len(),
+            pending,
+            species: Vec::new(),
+            inno_head,
+            rng,
+            config,
+            generation: 0,
+            lineage: Vec::new(),
+            specie_head: 0,
+            objectives: Vec::new(),
+            archive: Archive::new(),
+            hall_of_fame: HallOfFame::new(),
+            threshold: None,
+            non_finite: 0,
+            timings: Timings::default(),
+            innovations: 0,
+            best: None,
+            #[cfg(feature = "parallel")]
+            thread_pool: thread_pool(),
+        }
+    }
+
+    /// Genomes of the current generation who are waiting to be evaluated
+    pub fn ask(&self) -> &[G] {
+        &self.pending
+    }
+
+    /// Speciate some genomes into the most recently evaluated generation alongside its own,
+    /// like migrants from another population. Objectives of a multi-objective generation are
+    /// unknown for immigrants, and so they're forgotten.
+    pub fn immigrate(&mut self, genomes: impl IntoIterator<Item = (G, f64)>) {
+        let threshold = self.threshold();
+        speciate_into(
+            &mut self.species,
+            genomes.into_iter(),
+            threshold,
+            &mut self.specie_head,
+            self.generation,
+        );
+        self.objectives = Vec::new();
+        self.improve();
+    }
+
+    fn speciate_behavior(
+        &mut self,
+        genomes: Vec<(G, (f64, Vec<f64>))>,
+        speciation: &impl Speciation<C, G>,
+    ) {
+        let start = Instant::now();
+        let (genomes, evaluations): (Vec<_>, Vec<_>) = genomes.into_iter().unzip();
+        let (fitness, behaviors): (Vec<_>, Vec<_>) = evaluations.into_iter().unzip();
+
+        let blend = self.config.novelty.blend;
+        let novelty = self.archive.score(&behaviors, &self.config.novelty);
+        let (genomes, non_finite) = self.config.fitness_policy.apply_multi(
+            genomes
+                .into_iter()
+                .zip(fitness.into_iter().zip(novelty))
+                .map(|(genome, (fitness, novelty))| {
+                    (genome, [(1. - blend) * fitness + blend * novelty, fitness])
+                })
+                .collect(),
+        );
+        self.non_finite = non_finite;
+        let (genomes, scores): (Vec<_>, Vec<_>) = genomes.into_iter().unzip();
+
+        // speciate by index as a stand-in fitness, so that members can find their scores
+        self.partition(
+            genomes
+                .into_iter()
+                .enumerate()
+                .map(|(idx, genome)| (genome, idx as f64))
+                .collect(),
+            speciation,
+        );
+        let fitness = self
+            .species
+            .iter_mut()
+            .map(|Specie { members, .. }| {
+                members
+                    .iter_mut()
+                    .map(|(_, score)| {
+                        let [blended, fitness] = scores[*score as usize];
+                        *score = blended;
+                        fitness
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+
+        self.improve_by(
+            fitness
+                .iter()
+                .map(|fitness| fitness.iter().copied().max_by(f64::total_cmp))
+                .collect(),
+        );
+        self.hall_of_fame.induct(
+            self.species
+                .iter()
+                .zip(&fitness)
+                .flat_map(|(Specie { members, .. }, fitness)| {
+                    members.iter().map(|(g, _)| g).zip(fitness.iter().copied())
+                }),
+            self.generation,
+            self.config.hall_of_fame.size,
+        );
+        self.timings.speciation = start.elapsed();
+    }
+
+    fn speciate_multi(&mut self, genomes: Vec<(G, Vec<f64>)>, speciation: &impl Speciation<C, G>) {
+        let start = Instant::now();
+        let (genomes, non_finite) = self.config.fitness_policy.apply_multi(genomes);
+        self.non_finite = non_finite;
+        let (genomes, mut objectives): (Vec<_>, Vec<_>) = genomes.into_iter().unzip();
+
+        // speciate by index as a stand-in fitness, so that members can find their objectives
+        self.partition(
+            genomes
+                .into_iter()
+                .enumerate()
+                .map(|(idx, genome)| (genome, idx as f64))
+                .collect(),
+            speciation,
+        );
+        self.objectives = self
+            .species
+            .iter_mut()
+            .map(|Specie { members, .. }| {
+                let objectives = members
+                    .iter()
+                    .map(|(_, idx)| take(&mut objectives[*idx as usize]))
+                    .collect::<Vec<_>>();
+                for ((_, fitness), rank) in members.iter_mut().zip(pareto::rank(&objectives)) {
+                    *fitness = rank;
+                }
+                objectives
+            })
+            .collect();
+        self.improve();
+        self.timings.speciation = start.elapsed();
+    }
+
+    /// Speciate a fitted generation once its fitness policy is applied, and induct its fittest
+    /// into the hall of fame
+    fn speciate(&mut self, genomes: Vec<(G, f64)>, speciation: &impl Speciation<C, G>) {
+        let start = Instant::now();
+        let (genomes, non_finite) = self.config.fitness_policy.apply(genomes);
+        self.non_finite = non_finite;
+        self.partition(genomes, speciation);
+        self.improve();
+        self.hall_of_fame.induct(
+            self.species
+                .iter()
+                .flat_map(|Specie { members, .. }| members.iter().map(|(g, f)| (g, *f))),
+            self.generation,
+            self.config.hall_of_fame.size,
+        );
+        self.timings.speciation = start.elapsed();
+    }
+
+    fn partition(&mut self, genomes: Vec<(G, f64)>, speciation: &impl Speciation<C, G>) {
+        let lineage = take(&mut self.lineage);
+        let threshold = self.threshold();
+        self.species = speciation.speciate(
+            genomes,
+            lineage,
+            threshold,
+            &mut self.specie_head,
+            self.generation,
+            &mut self.rng,
+        );
+        self.objectives = Vec::new();
+    }
+
+    /// Record the best of this generation as achieved by each specie. Ranks of a
+    /// multi-objective generation say nothing about whether a specie improved, and so its
+    /// first objective is used instead.
+    fn improve(&mut self) {
+        let best = self
+            .species
+            .iter()
+            .enumerate()
+            .map(|(idx, specie)| match self.objectives.get(idx) {
+                Some(objectives) => objectives.iter().map(|o| o[0]).max_by(f64::total_cmp),
+                None => specie
+                    .members
+                    .iter()
+                    .map(|(_, f)| *f)
+                    .max_by(f64::total_cmp),
+            })
+            .collect();
+        self.improve_by(best);
+    }
+
+    /// Record `best` as achieved by each specie this generation, if it has a best
+    fn improve_by(&mut self, best: Vec<Option<f64>>) {
+        let generation = self.generation;
+        for (specie, best) in self.species.iter_mut().zip(best) {
+            if let Some(best) = best {
+                specie.improve(best, generation);
+                self.best = Some(self.best.map_or(best, |b| b.max(best)));
+            }
+        }
+    }
+
+    /// Reproduce the evaluated generation into the next one, whose genomes are then available
+    /// from [ask](Evolver::ask). Species who haven't improved in a while are truncated to their
+    /// fittest couple of members before reproducing, and the fittest of the hall of fame may
+    /// be [reinjected](crate::config::HallOfFameConfig::reinject) in place of some offspring.
+    /// Fails if the current generation hasn't been told its fitness yet, or if it can't be
+    /// reproduced ( such as when none of it is left after the
+    /// [fitness policy](EvolutionConfig::fitness_policy) ), in which case nothing but the rng
+    /// has changed.
+    pub fn step(&mut self) -> Result<(), EvolutionError> {
+        let mut innogen = InnoGen::new(self.inno_head);
+        self.step_with(&mut innogen)
+    }
+
+    /// Like [step](Evolver::step), but drawing innovation ids from an existing [InnoGen], so
+    /// that evolvers stepping in the same generation may share them. Our innovation head is
+    /// left wherever `innogen` ends up.
+    pub fn step_with(&mut self, innogen: &mut InnoGen) -> Result<(), EvolutionError> {
+        let staged = self.stage(innogen)?;
+        self.commit(staged);
+        Ok(())
+    }
+
+    /// Reproduce the evaluated generation like [step_with](Evolver::step_with), but without
+    /// changing anything other than the rng until it's [committed](Evolver::commit), so that
+    /// several evolvers may step together or not at all
+    pub(crate) fn stage(&mut self, innogen: &mut InnoGen) -> Result<Staged<G>, EvolutionError> {
+        if !self.pending.is_empty() {
+            return Err(EvolutionError::Unevaluated {
+                pending: self.pending.len(),
+                generation: self.generation,
+            });
+        }
+
+        // such as when every genome was discarded by the fitness policy
+        if self.species.iter().all(Specie::is_empty) {
+            return Err(EvolutionError::TooFewMembers {
+                produce: self.population,
+                members: 0,
+            });
+        }
+
+        let threshold = match &self.config.threshold_control {
+            Some(control) => {
+                // species carried on from past generations without members don't count
+                let threshold = self.threshold();
+                let living = self.species.iter().filter(|s| !s.is_empty()).count();
+                Some(match living.cmp(&control.target) {
+                    Ordering::Greater => threshold + control.step,
+                    Ordering::Less => (threshold - control.step).max(control.min),
+                    Ordering::Equal => threshold,
+                })
+            }
+            None => self.threshold,
+        };
+
+        let start = Instant::now();
+        let head = innogen.head;
+        let gen_idx = self.generation;
+
+        let p_truncated = self
+            .species
+            .iter()
+            .map(|s| {
+                if s.stagnant(gen_idx) >= self.config.stagnation_limit && s.members.len() > 2 {
+                    let mut order = (0..s.members.len()).collect::<Vec<_>>();
+                    order.sort_by(|l, r| s.members[*r].1.total_cmp(&s.members[*l].1));
+                    Specie {
+                        members: order[..2]
+                            .iter()
+                            .map(|idx| s.members[*idx].clone())
+                            .collect(),
+                        ..s.lineage()
+                    }
+                } else {
+                    s.clone()
+                }
+            })
+            .collect::<Vec<_>>();
+
+        let mut pending = population_reproduce_with(
+            &p_truncated,
+            self.population,
+            innogen,
+            &mut self.rng,
+            &self.config,
+        )?;
+
+        let reinject = self
+            .config
+            .hall_of_fame
+            .reinject
+            .min(self.hall_of_fame.len())
+            .min(pending.len());
+        pending.truncate(pending.len() - reinject);
+        pending.extend(
+            self.hall_of_fame
+                .iter()
+                .take(reinject)
+                .map(|inductee| inductee.genome.clone()),
+        );
+
+        debug_assert!(!pending.is_empty(), "nobody past {gen_idx}");
+        Ok(Staged {
+            pending,
+            threshold,
+            inno_head: innogen.head,
+            innovations: innogen.head - head,
+            reproduction: start.elapsed(),
+        })
+    }
+
+    /// Move into the generation reproduced by [stage](Evolver::stage)
+    pub(crate) fn commit(&mut self, staged: Staged<G>) {
+        self.threshold = staged.threshold;
+        self.lineage = take(&mut self.species)
+            .iter()
+            .filter(|specie| !specie.is_empty())
+            .map(Specie::lineage)
+            .collect();
+        self.objectives = Vec::new();
+        self.pending = staged.pending;
+        self.inno_head = staged.inno_head;
+        self.innovations = staged.innovations;
+        self.timings.reproduction = staged.reproduction;
+        self.generation += 1;
+    }
+
+    /// Everything about the evaluated generation that [immigrate](Evolver::immigrate) changes
+    pub(crate) fn evaluated(&self) -> Evaluated<C, G> {
+        Evaluated {
+            species: self.species.clone(),
+            specie_head: self.specie_head,
+            objectives: self.objectives.clone(),
+            best: self.best,
+        }
+    }
+
+    /// Undo immigration back to some [evaluated](Evolver::evaluated) generation
+    pub(crate) fn restore(&mut self, evaluated: Evaluated<C, G>) {
+        self.species = evaluated.species;
+        self.specie_head = evaluated.specie_head;
+        self.objectives = evaluated.objectives;
+        self.best = evaluated.best;
+    }
+
+    /// [Stats] describing the most recently evaluated generation
+    pub fn stats(&self) -> Stats<'_, C, G> {
+        Stats {
+            generation: self.generation,
+            species: &self.species,
+            objectives: &self.objectives,
+            archive: &self.archive,
+            hall_of_fame: &self.hall_of_fame,
+            threshold: self.threshold(),
+            fitness_policy: self.config.fitness_policy,
+            non_finite: self.non_finite,
+            timings: self.timings,
+            innovations: self.innovations,
+            inno_head: self.inno_head,
+            best: self.best,
+        }
+    }
+
+    /// Species of the most recently evaluated generation, or nothing if the current generation
+    /// hasn't been evaluated yet
+    pub fn species(&self) -> &[Specie<C, G>] {
+        &self.species
+    }
+
+    /// Novel behaviors seen so far, when evaluated by a [BehaviorScenario]
+    pub fn archive(&self) -> &Archive {
+        &self.archive
+    }
+
+    /// Fittest distinct genomes seen so far. Generations evaluated by a [MultiObjective]
+    /// scenario are ranked only against themselves, and so are never inducted.
+    pub fn hall_of_fame(&self) -> &HallOfFame<G> {
+        &self.hall_of_fame
+    }
+
+    /// Compatibility threshold that genomes are speciated with. Unless adjusted by a
+    /// [controller](EvolutionConfig::threshold_control), this is the one configured.
+    pub fn threshold(&self) -> f64 {
+        self.threshold.unwrap_or(self.config.specie_threshold)
+    }
+
+    /// Number of genomes of the most recently evaluated generation whose fitness ( or any
+    /// objective ) wasn't finite, and so was handled by the
+    /// [fitness policy](EvolutionConfig::fitness_policy)
+    pub fn non_finite(&self) -> usize {
+        self.non_finite
+    }
+
+    pub fn config(&self) -> &EvolutionConfig {
+        &self.config
+    }
+
+    /// Mutable access to the config, so that it may be tuned in the middle of a run
+    pub fn config_mut(&mut self) -> &mut EvolutionConfig {
+        &mut self.config
+    }
+
+    pub fn generation(&self) -> usize {
+        self.generation
+    }
+
+    pub fn inno_head(&self) -> usize {
+        self.inno_head
+    }
+
+    /// Stop evolving, and take the most recently evaluated species and innovation head
+    pub fn into_parts(self) -> (Vec<Specie<C, G>>, usize) {
+        (self.species, self.inno_head)
+    }
+}
+
+impl<
+        #[cfg(not(feature = "parallel"))] C: Connection,
+        #[cfg(feature = "parallel")] C: Connection + Sync,
+        #[cfg(not(feature = "parallel"))] G: Genome<C>,
+        #[cfg(feature = "parallel")] G: Genome<C> + Send + Sync,
+        R: RngCore,
+    > Evolver<C, G, R>
+{
+    /// Speciation by the configured strategy, in parallel if that's how it's configured
+    #[cfg(not(feature = "parallel"))]
+    fn speciation(&self) -> SpeciationStrategy {
+        self.config.speciation
+    }
+
+    #[cfg(feature = "parallel")]
+    fn speciation(&self) -> Parallelized {
+        Parallelized(self.config.speciation)
+    }
+
+    /// Provide a fitness for each genome returned by [ask](Evolver::ask), in the same order.
+    /// The now fitted genomes are speciated, and visible through [species](Evolver::species)
+    /// until the next [step](Evolver::step). A generation may only be told once, until it's
+    /// stepped into the next.
+    pub fn tell(&mut self, fitness: impl IntoIterator<Item = f64>) -> Result<(), EvolutionError> {
+        if self.pending.is_empty() {
+            return Err(EvolutionError::AlreadyEvaluated {
+                generation: self.generation,
+            });
+        }
+
+        let fitness = fitness.into_iter().collect::<Vec<_>>();
+        if fitness.len() != self.pending.len() {
+            return Err(EvolutionError::Mismatched {
+                wanted: self.pending.len(),
+                got: fitness.len(),
+            });
+        }
+
+        let genomes = take(&mut self.pending).into_iter().zip(fitness).collect();
+        self.timings.evaluation = Duration::ZERO;
+        self.speciate(genomes, &self.speciation());
+        Ok(())
+    }
+
+    /// Like [tell](Evolver::tell), but providing several objectives for each genome rather
+    /// than a single fitness, as a [MultiObjective] scenario would. Every genome must have the
+    /// same number of objectives, and at least one.
+    pub fn tell_multi(
+        &mut self,
+        objectives: impl IntoIterator<Item = Vec<f64>>,
+    ) -> Result<(), EvolutionError> {
+        if self.pending.is_empty() {
+            return Err(EvolutionError::AlreadyEvaluated {
+                generation: self.generation,
+            });
+        }
+
+        let objectives = objectives.into_iter().collect::<Vec<_>>();
+        if objectives.len() != self.pending.len() {
+            return Err(EvolutionError::Mismatched {
+                wanted: self.pending.len(),
+                got: objectives.len(),
+            });
+        }
+
+        if let Some(w) = objectives.windows(2).find(|w| w[0].len() != w[1].len()) {
+            return Err(EvolutionError::Mismatched {
+                wanted: w[0].len(),
+                got: w[1].len(),
+            });
+        }
+
+        if objectives.first().is_some_and(Vec::is_empty) {
+            return Err(EvolutionError::Mismatched { wanted: 1, got: 0 });
+        }
+
+        let genomes = take(&mut self.pending)
+            .into_iter()
+            .zip(objectives)
+            .collect();
+        self.timings.evaluation = Duration::ZERO;
+        self.speciate_multi(genomes, &self.speciation());
+        Ok(())
+    }
+
+    /// Like [tell](Evolver::tell), but providing a behavior along with the fitness of each
+    /// genome, as a [BehaviorScenario] would. Each fitness is blended with the novelty of its
+    /// behavior, and novel behaviors are archived. Every behavior must be the same length.
+    ///
+    /// Members are reproduced by their blended score, but as novelty drifts with the archive,
+    /// species improve and the hall of fame inducts by fitness alone.
+    pub fn tell_behavior(
+        &mut self,
+        evaluations: impl IntoIterator<Item = (f64, Vec<f64>)>,
+    ) -> Result<(), EvolutionError> {
+        if self.pending.is_empty() {
+            return Err(EvolutionError::AlreadyEvaluated {
+                generation: self.generation,
+            });
+        }
+
+        let evaluations = evaluations.into_iter().collect::<Vec<_>>();
+        if evaluations.len() != self.pending.len() {
+            return Err(EvolutionError::Mismatched {
+                wanted: self.pending.len(),
+                got: evaluations.len(),
+            });
+        }
+
+        if let Some(w) = evaluations
+            .windows(2)
+            .find(|w| w[0].1.len() != w[1].1.len())
+        {
+            return Err(EvolutionError::Mismatched {
+                wanted: w[0].1.len(),
+                got: w[1].1.len(),
+            });
+        }
+
+        let genomes = take(&mut self.pending)
+            .into_iter()
+            .zip(evaluations)
+            .collect();
+        self.timings.evaluation = Duration::ZERO;
+        self.speciate_behavior(genomes, &self.speciation());
+        Ok(())
+    }
+
+    /// Evaluate every genome from [ask](Evolver::ask) with `scenario`, and
+    /// [tell](Evolver::tell) their fitnesses. If compiled with `--features parallel`, this is
+    /// done in a thread-pool of one thread per cpu on the host. A generation that's already
+    /// been evaluated is left as it is.
+    pub fn eval<
+        #[cfg(not(feature = "parallel"))] S: Scenario<C, G>,
+        #[cfg(feature = "parallel")] S: Scenario<C, G> + Sync,
+    >(
+        &mut self,
+        scenario: &S,
+    ) {
+        if self.pending.is_empty() {
+            return;
+        }
+
+        let start = Instant::now();
+        let pending = take(&mut self.pending);
+
+        #[cfg(not(feature = "parallel"))]
+        let genomes = pending
+            .into_iter()
+            .map(|genome| {
+                let fitness = scenario.eval(&genome);
+                (genome, fitness)
+            })
+            .collect();
+        #[cfg(feature = "parallel")]
+        let genomes = self.thread_pool.install(|| {
+            pending
+                .into_par_iter()
+                .map(|genome| {
+                    let fitness = scenario.eval(&genome);
+                    (genome, fitness)
+                })
+                .collect()
+        });
+
+        self.timings.evaluation = start.elapsed();
+        self.speciate(genomes, &self.speciation());
+    }
+
+    /// Like [eval](Evolver::eval), but evaluating every genome by a [MultiObjective] scenario
+    /// and [telling](Evolver::tell_multi) their objectives.
+    pub fn eval_multi<
+        #[cfg(not(feature = "parallel"))] S: MultiObjective<C, G>,
+        #[cfg(feature = "parallel")] S: MultiObjective<C, G> + Sync,
+    >(
+        &mut self,
+        scenario: &S,
+    ) {
+        if self.pending.is_empty() {
+            return;
+        }
+
+        let start = Instant::now();
+        let pending = take(&mut self.pending);
+
+        #[cfg(not(feature = "parallel"))]
+        let genomes = pending
+            .into_iter()
+            .map(|genome| {
+                let objectives = scenario.eval(&genome);
+                (genome, objectives)
+            })
+            .collect();
+        #[cfg(feature = "parallel")]
+        let genomes = self.thread_pool.install(|| {
+            pending
+                .into_par_iter()
+                .map(|genome| {
+                    let objectives = scenario.eval(&genome);
+                    (genome, objectives)
+                })
+                .collect()
+        });
+
+        self.timings.evaluation = start.elapsed();
+        self.speciate_multi(genomes, &self.speciation());
+    }
+
+    /// Like [eval](Evolver::eval), but evaluating every genome by a [BehaviorScenario] and
+    /// [telling](Evolver::tell_behavior) their fitness and behavior.
+    pub fn eval_behavior<
+        #[cfg(not(feature = "parallel"))] S: BehaviorScenario<C, G>,
+        #[cfg(feature = "parallel")] S: BehaviorScenario<C, G> + Sync,
+    >(
+        &mut self,
+        scenario: &S,
+    ) {
+        if self.pending.is_empty() {
+            return;
+        }
+
+        let start = Instant::now();
+        let pending = take(&mut self.pending);
+
+        #[cfg(not(feature = "parallel"))]
+        let genomes = pending
+            .into_iter()
+            .map(|genome| {
+                let evaluation = scenario.eval(&genome);
+                (genome, evaluation)
+            })
+            .collect();
+        #[cfg(feature = "parallel")]
+        let genomes = self.thread_pool.install(|| {
+            pending
+                .into_par_iter()
+                .map(|genome| {
+                    let evaluation = scenario.eval(&genome);
+                    (genome, evaluation)
+                })
+                .collect()
+        });
+
+        self.timings.evaluation = start.elapsed();
+        self.speciate_behavior(genomes, &self.speciation());
+    }
+}
+
+impl<C: Connection, G: Genome<C>, R: RngCore + Serialize + for<'de> Deserialize<'de>>
+    Evolver<C, G, R>
+{
+    /// Serialize this evolver to a JSON string
+    pub fn to_string(&self) -> Result<String, Box<dyn Error>> {
+        Ok(serde_json::to_string(self)?)
+    }
+
+    /// Deserialize an evolver from a JSON string
+    #[allow(clippy::should_implement_trait)]
+    pub fn from_str(s: &str) -> Result<Self, Box<dyn Error>> {
+        serde_json::from_str(s).map_err(|op| op.into())
+    }
+
+    /// Checkpoint the complete evolution state to a file at `path`
+    pub fn to_file<P: AsRef<Path>>(&self, path: P) -> Result<(), Box<dyn Error>> {
+        fs::write(path, self.to_string()?)?;
+        Ok(())
+    }
+
+    /// Resume evolution from a checkpoint written by [to_file](Evolver::to_file)
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self, Box<dyn Error>> {
+        Self::from_str(&fs::read_to_string(path)?)
+    }
 }
 
 /// Given a well-defined evolution scenario, evolve is the entrypoint into actually... evolving.
 /// It will manage evaluation, speciation, reproduction, and mutation of a pool of genomes
-/// about ( but not necessarily exactly ) `population` large. Each specie is allocated some size
-/// in terms of `population`.
+/// exactly as large as the one returned by `init`. Each specie is allocated some share of that
+/// size by [population_alloc](crate::reproduce::population_alloc). How each of those happen is
+/// parameterized by `config`. For finer
+/// control over each generation, see [Evolver]. Fails if some generation can't be reproduced.
 ///
 /// If compiled with `--features parallel`, evaluation will run in a thread-pool of one thread
 /// per cpu on the host. This in turn requires our arguments ( excluding init, which is called
 /// exactly once ) to implement [Sync]
 pub fn evolve<
-    C: Connection,
+    #[cfg(not(feature = "parallel"))] C: Connection,
+    #[cfg(feature = "parallel")] C: Connection + Sync,
     #[cfg(not(feature = "parallel"))] G: Genome<C>,
-    #[cfg(feature = "parallel")] G: Genome<C> + Send,
+    #[cfg(feature = "parallel")] G: Genome<C> + Send + Sync,
     I: FnOnce((usize, usize)) -> (Vec<Specie<C, G>>, usize),
-    #[cfg(not(feature = "parallel"))] A: Fn(f64) -> f64,
-    #[cfg(feature = "parallel")] A: Fn(f64) -> f64 + Sync,
-    #[cfg(not(feature = "parallel"))] S: Scenario<C, G, A>,
-    #[cfg(feature = "parallel")] S: Scenario<C, G, A> + Sync,
+    #[cfg(not(feature = "parallel"))] S: Scenario<C, G>,
+    #[cfg(feature = "parallel")] S: Scenario<C, G> + Sync,
 >(
     scenario: S,
     init: I,
-    σ: A,
-    mut rng: impl RngCore,
+    config: EvolutionConfig,
+    rng: impl RngCore,
     hooks: EvolutionHooks<C, G>,
-) -> (Vec<Specie<C, G>>, usize) {
-    let (mut pop_flat, mut inno_head) = {
-        let (species, inno_head) = init(scenario.io());
-        (
-            species
-                .iter()
-                .flat_map(|Specie { members, .. }| members.iter().map(|(genome, _)| genome.clone()))
-                .collect::<Vec<_>>(),
-            inno_head,
-        )
-    };
+) -> Result<(Vec<Specie<C, G>>, usize), EvolutionError> {
+    let mut evolver = Evolver::new(init(scenario.io()), config, rng);
+    loop {
+        evolver.eval(&scenario);
+        if hooks.fire(evolver.stats()).is_break() {
+            break Ok(evolver.into_parts());
+        }
 
-    #[cfg(feature = "parallel")]
-    let thread_pool = ThreadPoolBuilder::new().build().unwrap();
-    let population_lim = pop_flat.len();
+        evolver.step()?;
+    }
+}
 
-    let mut scores: HashMap<SpecieRepr<C>, _> = HashMap::new();
-    let mut gen_idx = 0;
+/// Like [evolve], but for a [MultiObjective] scenario. The pareto front of each generation is
+/// visible to hooks through [Stats::pareto_front].
+pub fn evolve_multi<
+    #[cfg(not(feature = "parallel"))] C: Connection,
+    #[cfg(feature = "parallel")] C: Connection + Sync,
+    #[cfg(not(feature = "parallel"))] G: Genome<C>,
+    #[cfg(feature = "parallel")] G: Genome<C> + Send + Sync,
+    I: FnOnce((usize, usize)) -> (Vec<Specie<C, G>>, usize),
+    #[cfg(not(feature = "parallel"))] S: MultiObjective<C, G>,
+    #[cfg(feature = "parallel")] S: MultiObjective<C, G> + Sync,
+>(
+    scenario: S,
+    init: I,
+    config: EvolutionConfig,
+    rng: impl RngCore,
+    hooks: EvolutionHooks<C, G>,
+) -> Result<(Vec<Specie<C, G>>, usize), EvolutionError> {
+    let mut evolver = Evolver::new(init(scenario.io()), config, rng);
     loop {
-        let species = {
-            #[cfg(not(feature = "parallel"))]
-            let genomes = pop_flat.into_iter().map(|genome| {
-                let fitness = scenario.eval(&genome, &σ);
-                (genome, fitness)
-            });
-            #[cfg(feature = "parallel")]
-            let genomes = thread_pool.install(|| {
-                pop_flat
-                    .into_par_iter()
-                    .map(|genome| {
-                        let fitness = scenario.eval(&genome, &σ);
-                        (genome, fitness)
-                    })
-                    .collect::<Vec<_>>()
-                    .into_iter()
-            });
-            let reprs = scores.keys().cloned();
-
-            #[cfg(not(feature = "smol_bench"))]
-            let species = speciate(genomes, reprs);
-            #[cfg(feature = "smol_bench")]
-            let species = speciate(
-                genomes.collect::<Vec<_>>().into_iter(),
-                reprs.collect::<Vec<_>>().into_iter(),
-            );
-            species
-        };
+        evolver.eval_multi(&scenario);
+        if hooks.fire(evolver.stats()).is_break() {
+            break Ok(evolver.into_parts());
+        }
+
+        evolver.step()?;
+    }
+}
+
+/// Like [evolve], but for a [BehaviorScenario], searching for novelty as well as fitness. The
+/// archive of novel behaviors is visible to hooks through [Stats::archive].
+pub fn evolve_behavior<
+    #[cfg(not(feature = "parallel"))] C: Connection,
+    #[cfg(feature = "parallel")] C: Connection + Sync,
+    #[cfg(not(feature = "parallel"))] G: Genome<C>,
+    #[cfg(feature = "parallel")] G: Genome<C> + Send + Sync,
+    I: FnOnce((usize, usize)) -> (Vec<Specie<C, G>>, usize),
+    #[cfg(not(feature = "parallel"))] S: BehaviorScenario<C, G>,
+    #[cfg(feature = "parallel")] S: BehaviorScenario<C, G> + Sync,
+>(
+    scenario: S,
+    init: I,
+    config: EvolutionConfig,
+    rng: impl RngCore,
+    hooks: EvolutionHooks<C, G>,
+) -> Result<(Vec<Specie<C, G>>, usize), EvolutionError> {
+    let mut evolver = Evolver::new(init(scenario.io()), config, rng);
+    loop {
+        evolver.eval_behavior(&scenario);
+        if hooks.fire(evolver.stats()).is_break() {
+            break Ok(evolver.into_parts());
+        }
+
+        evolver.step()?;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        activate::relu,
+        config::{HallOfFameConfig, MutationConfig, NoveltyConfig, ThresholdControl},
+        genome::{Recurrent, WConnection},
+        macros::fixtures::{weighted, WeightShape, WeightSum},
+        population::{population_init, SpecieRepr},
+        random::{default_rng, WyRng},
+        test_t,
+    };
+    use std::collections::HashMap;
+
+    type C = WConnection;
+    type G = Recurrent<C>;
+
+    /// Weight sum, traded off against the number of enabled connections
+    struct WeightSize;
+
+    impl<C: Connection, G: Genome<C>> MultiObjective<C, G> for WeightSize {
+        fn io(&self) -> (usize, usize) {
+            (2, 1)
+        }
+
+        fn eval(&self, genome: &G) -> Vec<f64> {
+            let enabled = genome.connections().iter().filter(|c| c.enabled());
+            vec![
+                enabled.clone().map(|c| relu(c.weight())).sum(),
+                -(enabled.count() as f64),
+            ]
+        }
+    }
 
-        if hooks
-            .fire(Stats {
-                generation: gen_idx,
-                species: &species,
+    test_t!(test_evolver_ask_tell_step[T: G]() {
+        let count = 20;
+        let mut evolver = Evolver::new(
+            population_init::<C, T>(2, 1, count),
+            EvolutionConfig::default(),
+            default_rng(),
+        );
+        assert_eq!(evolver.ask().len(), count);
+        assert!(evolver.species().is_empty());
+        assert_eq!(
+            evolver.step(),
+            Err(EvolutionError::Unevaluated {
+                pending: count,
+                generation: 0
             })
-            .is_break()
-        {
-            break (species, inno_head);
+        );
+
+        for generation in 0..5 {
+            assert_eq!(evolver.generation(), generation);
+            let fitness = (0..evolver.ask().len()).map(|idx| idx as f64).collect::<Vec<_>>();
+            evolver.tell(fitness).unwrap();
+
+            assert!(evolver.ask().is_empty());
+            assert!(!evolver.species().is_empty());
+            evolver.step().unwrap();
+            assert!(!evolver.ask().is_empty());
         }
+    });
 
-        let scores_prev = scores;
-        scores = species
-            .iter()
-            .filter_map(|Specie { repr, members, .. }| {
-                let gen_max = members.iter().max_by(|(_, l), (_, r)| {
-                    l.partial_cmp(r)
-                        .unwrap_or_else(|| panic!("cannot partial_cmp {l} and {r}"))
-                });
-                let past_max = scores_prev.get(repr);
-
-                match (gen_max, past_max) {
-                    (Some((_, gen_max)), Some((past_max, past_idx))) => {
-                        if gen_max > past_max {
-                            Some((repr.clone(), (*gen_max, gen_idx)))
-                        } else {
-                            Some((repr.clone(), (*past_max, *past_idx)))
-                        }
-                    }
-                    (Some((_, gen_max)), None) => Some((repr.clone(), (*gen_max, gen_idx))),
-                    (None, _) => None,
-                }
+    test_t!(test_evolver_tell_twice[T: G]() {
+        let mut evolver = Evolver::new(
+            population_init::<C, T>(2, 1, 20),
+            EvolutionConfig::default(),
+            default_rng(),
+        );
+        evolver.eval(&WeightSum);
+        assert!(!evolver.species().is_empty());
+        let species = serde_json::to_value(evolver.species()).unwrap();
+
+        let evaluated = Err(EvolutionError::AlreadyEvaluated { generation: 0 });
+        assert_eq!(evolver.tell([]), evaluated);
+        assert_eq!(evolver.tell_multi([]), evaluated);
+        assert_eq!(evolver.tell_behavior([]), evaluated);
+        evolver.eval(&WeightSum);
+        evolver.eval_multi(&WeightSize);
+        evolver.eval_behavior(&WeightShape);
+        assert_eq!(serde_json::to_value(evolver.species()).unwrap(), species);
+        evolver.step().unwrap();
+    });
+
+    test_t!(test_evolver_step_nan[T: G]() {
+        let count = 20;
+        let mut evolver = Evolver::new(
+            population_init::<C, T>(2, 1, count),
+            EvolutionConfig {
+                fitness_policy: FitnessPolicy::Penalize { fitness: f64::NAN },
+                ..EvolutionConfig::default()
+            },
+            default_rng(),
+        );
+        evolver.tell(vec![f64::NAN; count]).unwrap();
+        assert!(matches!(
+            evolver.step(),
+            Err(EvolutionError::Incomparable(fit)) if fit.is_nan()
+        ));
+
+        let mut evolver = Evolver::new(
+            population_init::<C, T>(2, 1, count),
+            EvolutionConfig {
+                fitness_policy: FitnessPolicy::Discard,
+                ..EvolutionConfig::default()
+            },
+            default_rng(),
+        );
+        evolver.tell(vec![f64::NAN; count]).unwrap();
+        assert_eq!(evolver.non_finite(), count);
+        assert_eq!(
+            evolver.step(),
+            Err(EvolutionError::TooFewMembers {
+                produce: count,
+                members: 0
             })
-            .collect();
+        );
+    });
 
-        let p_truncated = species
-            .into_iter()
-            .map(|s| {
-                let (_, gen_achieved) = *scores_prev.get(&s.repr).unwrap_or(&(f64::MIN, gen_idx));
+    test_t!(test_evolve_non_finite[T: G]() {
+        struct Erratic;
 
-                if gen_achieved + NO_IMPROVEMENT_TRUNCATE <= gen_idx && s.members.len() > 2 {
-                    Specie {
-                        repr: s.repr,
-                        members: {
-                            let mut trunc = s.members;
-                            trunc.sort_by(|(_, l), (_, r)| {
-                                r.partial_cmp(l)
-                                    .unwrap_or_else(|| panic!("cannot partial_cmp {l} and {r}"))
-                            });
-                            trunc[..2].to_vec()
-                        },
-                    }
-                } else {
-                    s
+        impl<C: Connection, G: Genome<C>> Scenario<C, G> for Erratic {
+            fn io(&self) -> (usize, usize) {
+                (2, 1)
+            }
+
+            fn eval(&self, genome: &G) -> f64 {
+                let weight = genome.connections().iter().map(|c| c.weight()).sum::<f64>();
+                match genome.connections().len() % 4 {
+                    0 => f64::NAN,
+                    1 => f64::INFINITY,
+                    2 => f64::NEG_INFINITY,
+                    _ => weight,
                 }
-            })
-            .collect::<Vec<_>>();
+            }
+        }
 
-        (pop_flat, inno_head) =
-            population_reproduce(&p_truncated, population_lim, inno_head, &mut rng);
-        debug_assert!(!pop_flat.is_empty(), "nobody past {gen_idx}");
-        gen_idx += 1
-    }
+        for policy in [
+            FitnessPolicy::Clamp,
+            FitnessPolicy::Discard,
+            FitnessPolicy::Penalize { fitness: -1. },
+        ] {
+            let count = 40;
+            let mut evolver = Evolver::new(
+                population_init::<C, T>(2, 1, count),
+                EvolutionConfig {
+                    fitness_policy: policy,
+                    ..EvolutionConfig::default()
+                },
+                default_rng(),
+            );
+            for _ in 0..10 {
+                let expect = evolver
+                    .ask()
+                    .iter()
+                    .filter(|g| g.connections().len() % 4 != 3)
+                    .count();
+                evolver.eval(&Erratic);
+
+                let stats = evolver.stats();
+                assert_eq!(stats.fitness_policy, policy);
+                assert_eq!(stats.non_finite, expect);
+                assert!(stats
+                    .species
+                    .iter()
+                    .flat_map(|s| s.members.iter())
+                    .all(|(_, fit)| fit.is_finite()));
+                if policy == FitnessPolicy::Discard && expect == evolver.population {
+                    break;
+                }
+                assert!(stats.fittest().is_some_and(|(_, fit)| fit.is_finite()));
+
+                evolver.step().unwrap();
+            }
+        }
+    });
+
+    test_t!(test_stats[T: G]() {
+        let count = 20;
+        let (species, inno_head) = population_init::<C, T>(2, 1, count);
+        let mut evolver = Evolver::new((species, inno_head), EvolutionConfig::default(), default_rng());
+        let (nodes, connections) = (
+            evolver.ask()[0].nodes().len() as f64,
+            evolver.ask()[0].connections().len() as f64,
+        );
+        evolver.tell((0..count).map(|idx| idx as f64)).unwrap();
+
+        let stats = evolver.stats();
+        assert_eq!(stats.fitness_mean(), Some(9.5));
+        assert_eq!(stats.fitness_median(), Some(9.5));
+        assert!((stats.fitness_stddev().unwrap() - 33.25_f64.sqrt()).abs() < 1e-9);
+        assert_eq!(stats.nodes_mean(), Some(nodes));
+        assert_eq!(stats.connections_mean(), Some(connections));
+        assert!(stats.ages().iter().all(|age| *age == 0));
+        assert!(stats.stagnation().iter().all(|stagnant| *stagnant == 0));
+        assert_eq!(stats.best, Some(19.));
+        assert_eq!(stats.innovations, 0);
+        assert_eq!(stats.inno_head, inno_head);
+        assert_eq!(stats.timings.evaluation, Duration::ZERO);
+
+        for _ in 0..5 {
+            evolver.step().unwrap();
+            evolver.eval(&WeightSum);
+        }
+
+        let stats = evolver.stats();
+        assert_eq!(stats.generation, 5);
+        assert_eq!(stats.inno_head, evolver.inno_head());
+        assert!(stats.innovations <= stats.inno_head - inno_head);
+        assert!(stats.timings.reproduction > Duration::ZERO);
+        assert!(stats.best.unwrap() >= 19.);
+        for (specie, (age, stagnant)) in stats
+            .species
+            .iter()
+            .zip(stats.ages().into_iter().zip(stats.stagnation()))
+        {
+            assert_eq!(age, 5 - specie.born);
+            assert_eq!(stagnant, 5 - specie.improved);
+            assert!(stagnant <= age);
+        }
+    });
+
+    test_t!(test_stats_empty[T: G]() {
+        let evolver = Evolver::new(
+            population_init::<C, T>(2, 1, 10),
+            EvolutionConfig::default(),
+            default_rng(),
+        );
+        let stats = evolver.stats();
+        assert_eq!(stats.fitness_mean(), None);
+        assert_eq!(stats.fitness_median(), None);
+        assert_eq!(stats.fitness_stddev(), None);
+        assert_eq!(stats.nodes_mean(), None);
+        assert_eq!(stats.best, None);
+    });
+
+    test_t!(test_evolver_tell_mismatched[T: G]() {
+        let mut evolver = Evolver::new(
+            population_init::<C, T>(2, 1, 10),
+            EvolutionConfig::default(),
+            default_rng(),
+        );
+        assert_eq!(
+            evolver.tell(vec![0.; 9]),
+            Err(EvolutionError::Mismatched { wanted: 10, got: 9 })
+        );
+        assert_eq!(
+            evolver.tell(vec![0.; 11]),
+            Err(EvolutionError::Mismatched { wanted: 10, got: 11 })
+        );
+        assert_eq!(evolver.ask().len(), 10);
+        assert!(evolver.tell(vec![0.; 10]).is_ok());
+    });
+
+    test_t!(test_evolver_resume[T: G]() {
+        let mut evolver = Evolver::new(
+            population_init::<C, T>(2, 1, 20),
+            EvolutionConfig::default(),
+            WyRng::seeded(0xeeee),
+        );
+        for _ in 0..5 {
+            evolver.eval(&WeightSum);
+            evolver.step().unwrap();
+        }
+
+        // checkpoint both between generations, and between evaluation and reproduction
+        let mut resumed = Evolver::<C, T, WyRng>::from_str(&evolver.to_string().unwrap()).unwrap();
+        for _ in 0..5 {
+            assert_eq!(
+                serde_json::to_string(evolver.ask()).unwrap(),
+                serde_json::to_string(resumed.ask()).unwrap()
+            );
+            evolver.eval(&WeightSum);
+            resumed.eval(&WeightSum);
+
+            let mut resumed_mid = Evolver::<C, T, WyRng>::from_str(&resumed.to_string().unwrap()).unwrap();
+            assert_eq!(
+                serde_json::to_string(evolver.species()).unwrap(),
+                serde_json::to_string(resumed_mid.species()).unwrap()
+            );
+            evolver.step().unwrap();
+            resumed_mid.step().unwrap();
+            resumed = resumed_mid;
+        }
+
+        assert_eq!(evolver.generation(), resumed.generation());
+        assert_eq!(evolver.inno_head(), resumed.inno_head());
+        assert_eq!(evolver.to_string().unwrap(), resumed.to_string().unwrap());
+    });
+
+    test_t!(test_evolver_step_failed[T: G]() {
+        // nowhere to connect, and so every mutated child fails
+        let (genome, inno_head) = T::new(0, 0);
+        assert!(genome.connections().is_empty());
+        let specie = Specie {
+            members: vec![(genome, 0.); 4],
+            ..Specie::new(SpecieRepr::new(vec![]), 0, 0)
+        };
+        let mut evolver = Evolver::new(
+            (vec![specie], inno_head),
+            EvolutionConfig {
+                threshold_control: Some(ThresholdControl::default()),
+                ..EvolutionConfig::default()
+            },
+            WyRng::seeded(0),
+        );
+        evolver.tell([1., 2., 3., 4.]).unwrap();
+
+        // the rng is left wherever reproduction got to, but nothing else may change
+        let state = |evolver: &Evolver<C, T, WyRng>| {
+            let mut state = serde_json::from_str::<serde_json::Value>(&evolver.to_string().unwrap()).unwrap();
+            state.as_object_mut().unwrap().remove("rng");
+            state
+        };
+        let before = state(&evolver);
+        for _ in 0..2 {
+            assert_eq!(evolver.step(), Err(EvolutionError::Saturated));
+            assert_eq!(state(&evolver), before);
+            assert_eq!(evolver.species().iter().map(|s| s.len()).sum::<usize>(), 4);
+        }
+
+        let mut resumed = Evolver::<C, T, WyRng>::from_str(&evolver.to_string().unwrap()).unwrap();
+        assert_eq!(state(&resumed), before);
+        assert_eq!(resumed.step(), Err(EvolutionError::Saturated));
+    });
+
+    test_t!(test_evolver_multi[T: G]() {
+        let mut evolver = Evolver::new(
+            population_init::<C, T>(2, 1, 30),
+            EvolutionConfig::default(),
+            WyRng::seeded(0xeeee),
+        );
+        assert_eq!(
+            evolver.tell_multi(vec![vec![0.]; 29]),
+            Err(EvolutionError::Mismatched { wanted: 30, got: 29 })
+        );
+        assert_eq!(
+            evolver.tell_multi((0..30).map(|idx| vec![0.; 1 + idx % 2])),
+            Err(EvolutionError::Mismatched { wanted: 1, got: 2 })
+        );
+        assert_eq!(
+            evolver.tell_multi(vec![vec![]; 30]),
+            Err(EvolutionError::Mismatched { wanted: 1, got: 0 })
+        );
+        assert_eq!(evolver.ask().len(), 30);
+
+        for _ in 0..10 {
+            evolver.eval_multi(&WeightSize);
+            let stats = evolver.stats();
+            for ((_, fitness), objectives) in stats
+                .species
+                .iter()
+                .flat_map(|Specie { members, .. }| members)
+                .zip(stats.objectives.iter().flatten())
+            {
+                assert!(*fitness > 0. && *fitness <= 1.);
+                assert_eq!(objectives.len(), 2);
+            }
+
+            let front = stats.pareto_front();
+            assert!(!front.is_empty());
+            for (_, l) in front.iter() {
+                assert!(stats
+                    .objectives
+                    .iter()
+                    .flatten()
+                    .all(|r| !pareto::dominates(r, l)));
+            }
+
+            let resumed = Evolver::<C, T, WyRng>::from_str(&evolver.to_string().unwrap()).unwrap();
+            assert_eq!(resumed.stats().pareto_front().len(), front.len());
+            evolver.step().unwrap();
+        }
+
+        evolver.eval(&WeightSum);
+        assert!(evolver.stats().objectives.is_empty());
+        assert!(evolver.stats().pareto_front().is_empty());
+    });
+
+    test_t!(test_evolver_behavior[T: G]() {
+        let mut evolver = Evolver::new(
+            population_init::<C, T>(2, 1, 30),
+            EvolutionConfig {
+                novelty: NoveltyConfig {
+                    k: 5,
+                    archive_threshold: 0.5,
+                    ..NoveltyConfig::default()
+                },
+                ..EvolutionConfig::default()
+            },
+            WyRng::seeded(0xeeee),
+        );
+        assert_eq!(
+            evolver.tell_behavior(vec![(0., vec![0.]); 29]),
+            Err(EvolutionError::Mismatched { wanted: 30, got: 29 })
+        );
+        assert_eq!(
+            evolver.tell_behavior((0..30).map(|idx| (0., vec![0.; 1 + idx % 2]))),
+            Err(EvolutionError::Mismatched { wanted: 1, got: 2 })
+        );
+
+        for _ in 0..10 {
+            evolver.eval_behavior(&WeightShape);
+            evolver.step().unwrap();
+        }
+        evolver.eval_behavior(&WeightShape);
+        assert!(!evolver.archive().is_empty());
+        assert_eq!(evolver.stats().archive, evolver.archive());
+
+        assert!(evolver
+            .species()
+            .iter()
+            .flat_map(|Specie { members, .. }| members)
+            .all(|(_, fitness)| fitness.is_finite() && *fitness >= 0.));
+
+        let resumed = Evolver::<C, T, WyRng>::from_str(&evolver.to_string().unwrap()).unwrap();
+        assert_eq!(resumed.archive(), evolver.archive());
+
+        // novelty is blended into members, but bests and the hall of fame keep raw fitness
+        let mut evolver = Evolver::new(
+            population_init::<C, T>(2, 1, 30),
+            EvolutionConfig::default(),
+            WyRng::seeded(0xeeee),
+        );
+        evolver
+            .tell_behavior((0..30).map(|idx| (0., vec![idx as f64])))
+            .unwrap();
+        assert!(evolver
+            .species()
+            .iter()
+            .flat_map(|Specie { members, .. }| members)
+            .any(|(_, fitness)| *fitness > 0.));
+        assert!(evolver.species().iter().all(|specie| specie.best == 0.));
+        assert_eq!(evolver.stats().best, Some(0.));
+        assert!(!evolver.hall_of_fame().is_empty());
+        assert!(evolver.hall_of_fame().iter().all(|i| i.fitness == 0.));
+    });
+
+    test_t!(test_evolver_hall_of_fame[T: G]() {
+        let mut evolver = Evolver::new(
+            population_init::<C, T>(2, 1, 30),
+            EvolutionConfig {
+                hall_of_fame: HallOfFameConfig {
+                    size: 5,
+                    reinject: 2,
+                },
+                ..EvolutionConfig::default()
+            },
+            WyRng::seeded(0xeeee),
+        );
+
+        let mut best = f64::MIN;
+        for _ in 0..10 {
+            evolver.eval(&WeightSum);
+            let stats = evolver.stats();
+            let fittest = stats.hall_of_fame.fittest().unwrap();
+            assert!(fittest.fitness >= best);
+            assert!(fittest.fitness >= stats.fittest().unwrap().1);
+            assert!(stats.hall_of_fame.len() <= 5);
+            best = fittest.fitness;
+
+            let hall = stats
+                .hall_of_fame
+                .iter()
+                .take(2)
+                .map(|inductee| serde_json::to_string(&inductee.genome).unwrap())
+                .collect::<Vec<_>>();
+            evolver.step().unwrap();
+            let tail = evolver.ask()[evolver.ask().len() - hall.len()..]
+                .iter()
+                .map(|genome| serde_json::to_string(genome).unwrap())
+                .collect::<Vec<_>>();
+            assert_eq!(tail, hall);
+        }
+
+        let resumed = Evolver::<C, T, WyRng>::from_str(&evolver.to_string().unwrap()).unwrap();
+        assert_eq!(resumed.hall_of_fame().len(), evolver.hall_of_fame().len());
+    });
+
+    test_t!(test_evolver_threshold_control[T: G]() {
+        let control = ThresholdControl {
+            target: 4,
+            step: 0.5,
+            min: 0.1,
+        };
+        let mut evolver = Evolver::new(
+            population_init::<C, T>(2, 1, 50),
+            EvolutionConfig {
+                specie_threshold: 0.1,
+                threshold_control: Some(control.clone()),
+                ..EvolutionConfig::default()
+            },
+            WyRng::seeded(0xeeee),
+        );
+
+        let (mut raised, mut lowered) = (false, false);
+        for _ in 0..40 {
+            evolver.eval(&WeightSum);
+            let count = evolver.species().iter().filter(|s| !s.is_empty()).count();
+            let threshold = evolver.stats().threshold;
+            evolver.step().unwrap();
+
+            let expected = match count.cmp(&control.target) {
+                Ordering::Greater => threshold + control.step,
+                Ordering::Less => (threshold - control.step).max(control.min),
+                Ordering::Equal => threshold,
+            };
+            assert_eq!(evolver.threshold(), expected);
+            raised |= expected > threshold;
+            lowered |= expected < threshold;
+        }
+        assert!(raised && lowered);
+
+        let resumed = Evolver::<C, T, WyRng>::from_str(&evolver.to_string().unwrap()).unwrap();
+        assert_eq!(resumed.threshold(), evolver.threshold());
+
+        let fixed = Evolver::new(
+            population_init::<C, T>(2, 1, 10),
+            EvolutionConfig::default(),
+            WyRng::seeded(0xeeee),
+        );
+        assert_eq!(fixed.threshold(), EvolutionConfig::default().specie_threshold);
+    });
+
+    test_t!(test_evolver_threshold_control_empty[T: G]() {
+        // 4 genomes far enough apart to each form their own specie
+        let members = weighted::<T>([0., -100., -200., -300.]);
+        let (_, inno_head) = T::new(2, 1);
+        let specie = Specie {
+            members,
+            ..Specie::new(SpecieRepr::new(vec![]), 0, 0)
+        };
+        let control = ThresholdControl {
+            target: 3,
+            step: 0.5,
+            min: 0.,
+        };
+        let mut evolver = Evolver::new(
+            (vec![specie], inno_head),
+            EvolutionConfig {
+                specie_threshold: 1.,
+                threshold_control: Some(control.clone()),
+                mutation: MutationConfig {
+                    new_connection: 0,
+                    bisect_connection: 0,
+                    mutate_connection: 0,
+                    ..MutationConfig::default()
+                },
+                ..EvolutionConfig::default()
+            },
+            WyRng::seeded(0),
+        );
+        evolver.tell([1., 0., 0., 0.]).unwrap();
+        assert_eq!(evolver.species().len(), 4);
+
+        // only the fittest specie reproduces, and its unmutated children leave the rest empty
+        evolver.step().unwrap();
+        assert_eq!(evolver.threshold(), 1. + control.step);
+        evolver.tell([0.; 4]).unwrap();
+        assert_eq!(evolver.species().len(), 4);
+        assert_eq!(evolver.species().iter().filter(|s| !s.is_empty()).count(), 1);
+
+        evolver.step().unwrap();
+        assert_eq!(evolver.threshold(), 1.);
+    });
+
+    test_t!(test_evolver_specie_lineage[T: G]() {
+        let mut evolver = Evolver::new(
+            population_init::<C, T>(2, 1, 50),
+            EvolutionConfig {
+                specie_threshold: 1.,
+                ..EvolutionConfig::default()
+            },
+            WyRng::seeded(0xeeee),
+        );
+
+        let mut seen = HashMap::new();
+        for generation in 0..20 {
+            evolver.eval(&WeightSum);
+            for specie in evolver.stats().species {
+                assert!(specie.id < evolver.specie_head);
+                assert!(specie.born <= specie.improved && specie.improved <= generation);
+                assert!(specie
+                    .members
+                    .iter()
+                    .all(|(_, fitness)| *fitness <= specie.best));
+
+                // an identity once given out never changes its birth, nor gets worse
+                let (born, best) = seen.entry(specie.id).or_insert((specie.born, specie.best));
+                assert_eq!(*born, specie.born);
+                assert!(specie.best >= *best);
+                *best = specie.best;
+            }
+            evolver.step().unwrap();
+        }
+        assert!(seen.len() > 1);
+    });
+
+    test_t!(test_evolver_speciation[T: G]() {
+        for speciation in [
+            SpeciationStrategy::FirstMatch,
+            SpeciationStrategy::RandomRepr,
+            SpeciationStrategy::KMedoids {
+                k: 4,
+                iterations: 5,
+            },
+            SpeciationStrategy::Parallel,
+        ] {
+            let mut evolver = Evolver::new(
+                population_init::<C, T>(2, 1, 40),
+                EvolutionConfig {
+                    speciation,
+                    ..EvolutionConfig::default()
+                },
+                WyRng::seeded(0xeeee),
+            );
+            for _ in 0..10 {
+                let count = evolver.ask().len();
+                evolver.eval(&WeightSum);
+                assert_eq!(
+                    evolver.species().iter().map(Specie::len).sum::<usize>(),
+                    count,
+                    "{speciation:?}"
+                );
+                if let SpeciationStrategy::KMedoids { k, .. } = speciation {
+                    assert!(evolver.species().iter().filter(|s| !s.is_empty()).count() <= k);
+                }
+                evolver.step().unwrap();
+            }
+        }
+    });
 }
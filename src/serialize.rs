@@ -1,6 +1,6 @@
 //! Helpers for de/serializing NeuroEvoluiton components
 
-use crate::{genome::NodeKind, Connection};
+use crate::{genome::Node, Connection};
 use rulinalg::matrix::Matrix;
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
 
@@ -38,10 +38,39 @@ pub fn deserialize_matrix_square<'de, D: Deserializer<'de>>(
     })
 }
 
-pub fn deserialize_nodes<'de, D: Deserializer<'de>>(
+/// Kinds that nodes were once saved as, before they carried any params of their own
+#[derive(Deserialize)]
+enum NodeKind {
+    Sensory,
+    Action,
+    Internal,
+    Static,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum NodeOrKind<N> {
+    Node(N),
+    Kind(NodeKind),
+}
+
+/// Deserialize nodes, migrating those saved as a [NodeKind] into a default node, or a static
+/// node if they were static
+pub fn deserialize_nodes<'de, N: Node, D: Deserializer<'de>>(
     deserializer: D,
-) -> Result<Vec<NodeKind>, D::Error> {
-    Vec::<NodeKind>::deserialize(deserializer)
+) -> Result<Vec<N>, D::Error> {
+    Vec::<NodeOrKind<N>>::deserialize(deserializer).map(|nodes| {
+        nodes
+            .into_iter()
+            .map(|node| match node {
+                NodeOrKind::Node(node) => node,
+                NodeOrKind::Kind(NodeKind::Static) => N::new_static(),
+                NodeOrKind::Kind(NodeKind::Sensory | NodeKind::Action | NodeKind::Internal) => {
+                    N::default()
+                }
+            })
+            .collect()
+    })
 }
 
 pub fn deserialize_connections<'de, C: Connection, D: Deserializer<'de>>(
@@ -49,3 +78,14 @@ pub fn deserialize_connections<'de, C: Connection, D: Deserializer<'de>>(
 ) -> Result<Vec<C>, D::Error> {
     Vec::<C>::deserialize(deserializer)
 }
+
+pub fn serialize_bits<S: Serializer>(data: &[f64], serializer: S) -> Result<S::Ok, S::Error> {
+    data.iter()
+        .map(|&f| f64::to_bits(f))
+        .collect::<Vec<_>>()
+        .serialize(serializer)
+}
+
+pub fn deserialize_bits<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<f64>, D::Error> {
+    Vec::<u64>::deserialize(deserializer).map(|v| v.into_iter().map(f64::from_bits).collect())
+}